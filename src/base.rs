@@ -1,7 +1,7 @@
 use bevy::prelude::*;
 
 use crate::{
-    BASE_SIZE, GameTextures, SPRITE_SCALE,
+    BASE_COLLIDER, BASE_SIZE, GameTextures, SPRITE_SCALE,
     components::{Base, SpriteSize},
 };
 
@@ -22,5 +22,6 @@ fn base_spawn(mut commands: Commands, game_textures: Res<GameTextures>) {
             },
         ))
         .insert(Base)
-        .insert(SpriteSize::from(BASE_SIZE));
+        .insert(SpriteSize::from(BASE_SIZE))
+        .insert(BASE_COLLIDER);
 }
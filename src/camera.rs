@@ -0,0 +1,91 @@
+use bevy::{
+    input::mouse::{AccumulatedMouseScroll, MouseScrollUnit},
+    prelude::*,
+};
+
+use crate::{
+    CAMERA_FOLLOW_RATE, CAMERA_ZOOM, CAMERA_ZOOM_SPEED, MAX_CAMERA_ZOOM, MIN_CAMERA_ZOOM,
+    WORLD_SIZE, WorldMode, components::Player, input::ActionState, keymap::Action, wrap_offset,
+};
+
+#[derive(Resource)]
+pub struct CameraZoom(pub f32);
+
+pub struct CameraPlugin;
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(CameraZoom(CAMERA_ZOOM))
+            .add_systems(Update, (camera_zoom, camera_follow, draw_world_border));
+    }
+}
+
+// zoom with the zoom actions or the mouse wheel
+fn camera_zoom(
+    time: Res<Time<Real>>,
+    actions: Res<ActionState>,
+    scroll: Res<AccumulatedMouseScroll>,
+    mut zoom: ResMut<CameraZoom>,
+    mut camera_query: Query<&mut Projection, With<Camera2d>>,
+) {
+    let mut change = 0.0;
+    if actions.pressed(Action::ZoomIn) {
+        change -= CAMERA_ZOOM_SPEED * time.delta_secs();
+    }
+    if actions.pressed(Action::ZoomOut) {
+        change += CAMERA_ZOOM_SPEED * time.delta_secs();
+    }
+    change -= match scroll.unit {
+        MouseScrollUnit::Line => scroll.delta.y * 0.1,
+        MouseScrollUnit::Pixel => scroll.delta.y * 0.001,
+    };
+
+    if change != 0.0 {
+        zoom.0 = (zoom.0 * f32::exp(change)).clamp(MIN_CAMERA_ZOOM, MAX_CAMERA_ZOOM);
+    }
+    if !zoom.is_changed() {
+        return;
+    }
+
+    for mut projection in camera_query.iter_mut() {
+        if let Projection::Orthographic(orthographic) = &mut *projection {
+            orthographic.scale = zoom.0;
+        }
+    }
+}
+
+// smoothly follow the player
+fn camera_follow(
+    time: Res<Time>,
+    world_mode: Res<WorldMode>,
+    player_query: Query<&Transform, (With<Player>, Without<Camera2d>)>,
+    mut camera_query: Query<&mut Transform, With<Camera2d>>,
+) {
+    let Ok(player_tf) = player_query.single() else {
+        return;
+    };
+    let follow = 1.0 - f32::exp(-CAMERA_FOLLOW_RATE * time.delta_secs());
+
+    for mut camera_tf in camera_query.iter_mut() {
+        // jump along with the player when it crosses the seam
+        if *world_mode == WorldMode::Wrap {
+            let player_pos = player_tf.translation.truncate();
+            let position = player_pos + wrap_offset(camera_tf.translation.truncate() - player_pos);
+            camera_tf.translation.x = position.x;
+            camera_tf.translation.y = position.y;
+        }
+
+        let target = player_tf.translation.with_z(camera_tf.translation.z);
+        camera_tf.translation = camera_tf.translation.lerp(target, follow);
+    }
+}
+
+fn draw_world_border(world_mode: Res<WorldMode>, mut gizmos: Gizmos) {
+    if *world_mode != WorldMode::Bounded {
+        return;
+    }
+    gizmos.rect_2d(
+        Isometry2d::IDENTITY,
+        Vec2::from(WORLD_SIZE),
+        Color::srgba(0.4, 0.4, 0.5, 0.5),
+    );
+}
@@ -0,0 +1,431 @@
+use std::f32::consts::TAU;
+
+use bevy::{platform::collections::HashMap, prelude::*};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use crate::{
+    ASTEROID_BELT, BASE_CLEAR_RADIUS, BLACK_HOLE_WELL, BiomeDensity, CHUNK_LOAD_RADIUS, CHUNK_SIZE,
+    EMPTY_VOID, GameTextures, MIN_SPAWN_SPACING, ORE_FIELD, PLANET_WELL, SHIP_CLEAR_RADIUS,
+    SPAWN_ATTEMPTS, WORLD_SIZE, WorldMode,
+    components::{Base, Chunk, HazardKind, Movable, Player, RockType, Velocity},
+    gravity::{spawn_black_hole, spawn_planet},
+    hazards::spawn_hazard,
+    mining::spawn_ore,
+    rock::spawn_rock,
+    trades::ResourceType,
+    wrap_offset,
+};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Biome {
+    AsteroidBelt,
+    OreField,
+    EmptyVoid,
+}
+
+impl Biome {
+    const ALL: [Biome; 3] = [Biome::AsteroidBelt, Biome::OreField, Biome::EmptyVoid];
+
+    fn density(&self) -> &'static BiomeDensity {
+        match self {
+            Biome::AsteroidBelt => &ASTEROID_BELT,
+            Biome::OreField => &ORE_FIELD,
+            Biome::EmptyVoid => &EMPTY_VOID,
+        }
+    }
+}
+
+// every chunk is generated from this, so the same seed gives the same world
+#[derive(Resource)]
+pub struct WorldSeed(pub u64);
+
+// chunks around the player and their biome
+#[derive(Resource, Default)]
+pub struct LoadedChunks(pub HashMap<IVec2, Biome>);
+
+pub struct ChunkPlugin;
+impl Plugin for ChunkPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(WorldSeed(rand::random()))
+            .init_resource::<LoadedChunks>()
+            .add_systems(FixedUpdate, load_chunks);
+    }
+}
+
+pub fn chunk_coord(position: Vec2) -> IVec2 {
+    (position / CHUNK_SIZE).floor().as_ivec2()
+}
+
+// the same seed and chunk always give the same numbers
+fn chunk_rng(seed: u64, coord: IVec2) -> StdRng {
+    let x = coord.x as i64 as u64;
+    let y = coord.y as i64 as u64;
+    StdRng::seed_from_u64(
+        seed ^ x.wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ y.wrapping_mul(0xC2B2_AE3D_27D4_EB4F),
+    )
+}
+
+// in a wrapping world the chunks repeat every world size
+// so the same chunk is never generated twice
+fn chunk_key(world_mode: WorldMode, coord: IVec2) -> IVec2 {
+    let world_chunks = (Vec2::from(WORLD_SIZE) / CHUNK_SIZE)
+        .as_ivec2()
+        .max(IVec2::ONE);
+    match world_mode {
+        WorldMode::Endless | WorldMode::Bounded => coord,
+        WorldMode::Wrap => coord.rem_euclid(world_chunks),
+    }
+}
+
+fn in_world(world_mode: WorldMode, position: Vec2) -> bool {
+    let half_world = Vec2::from(WORLD_SIZE) / 2.0;
+    match world_mode {
+        WorldMode::Endless | WorldMode::Wrap => true,
+        WorldMode::Bounded => position.abs().cmplt(half_world).all(),
+    }
+}
+
+fn spawn_distance(world_mode: WorldMode, a: Vec2, b: Vec2) -> f32 {
+    match world_mode {
+        WorldMode::Endless | WorldMode::Bounded => a.distance(b),
+        WorldMode::Wrap => wrap_offset(a - b).length(),
+    }
+}
+
+// random spot in the chunk away from earlier spawns
+// half the spacing is kept off the chunk edges so neighbouring chunks can't overlap either
+// gives up after a few tries so crowded chunks just get fewer objects
+fn spaced_position(
+    rng: &mut StdRng,
+    world_mode: WorldMode,
+    origin: Vec2,
+    placed: &mut Vec<Vec2>,
+) -> Option<Vec2> {
+    let margin = MIN_SPAWN_SPACING / 2.0;
+    for _ in 0..SPAWN_ATTEMPTS {
+        let position = origin
+            + Vec2::new(
+                rng.random_range(margin..CHUNK_SIZE - margin),
+                rng.random_range(margin..CHUNK_SIZE - margin),
+            );
+        if !in_world(world_mode, position) {
+            continue;
+        }
+
+        if placed
+            .iter()
+            .all(|other| other.distance(position) > MIN_SPAWN_SPACING)
+        {
+            placed.push(position);
+            return Some(position);
+        }
+    }
+    None
+}
+
+// generate chunks as the player gets close
+// and clean them up again once they are far behind
+fn load_chunks(
+    mut commands: Commands,
+    game_textures: Res<GameTextures>,
+    seed: Res<WorldSeed>,
+    world_mode: Res<WorldMode>,
+    mut loaded: ResMut<LoadedChunks>,
+    player_query: Query<&Transform, With<Player>>,
+    base_query: Query<&Transform, With<Base>>,
+    chunk_query: Query<(Entity, &Chunk)>,
+) {
+    let Ok(player_tf) = player_query.single() else {
+        return;
+    };
+    let player_pos = player_tf.translation.truncate();
+    let center = chunk_coord(player_pos);
+
+    // nothing spawns right on top of the ship or the base
+    let mut zones = vec![(player_pos, SHIP_CLEAR_RADIUS)];
+    for base_tf in base_query {
+        zones.push((base_tf.translation.truncate(), BASE_CLEAR_RADIUS));
+    }
+
+    // unload one chunk further out than we load
+    // so flying along a chunk edge doesn't regenerate it over and over
+    let world_chunks = (Vec2::from(WORLD_SIZE) / CHUNK_SIZE)
+        .as_ivec2()
+        .max(IVec2::ONE);
+    let far = |coord: IVec2| {
+        let mut offset = coord - center;
+        if *world_mode == WorldMode::Wrap {
+            offset = (offset + world_chunks / 2).rem_euclid(world_chunks) - world_chunks / 2;
+        }
+        offset.abs().max_element() > CHUNK_LOAD_RADIUS + 1
+    };
+    // switching world modes starts over with fresh chunks
+    let reset = world_mode.is_changed();
+    loaded.0.retain(|coord, _| !reset && !far(*coord));
+    for (entity, chunk) in chunk_query {
+        if reset || far(chunk.0) {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    for x in -CHUNK_LOAD_RADIUS..=CHUNK_LOAD_RADIUS {
+        for y in -CHUNK_LOAD_RADIUS..=CHUNK_LOAD_RADIUS {
+            let coord = chunk_key(*world_mode, center + IVec2::new(x, y));
+            if loaded.0.contains_key(&coord) {
+                continue;
+            }
+            let biome = generate_chunk(
+                &mut commands,
+                &game_textures,
+                *world_mode,
+                seed.0,
+                coord,
+                &zones,
+            );
+            loaded.0.insert(coord, biome);
+        }
+    }
+}
+
+// everything in a chunk, decided only by the seed and the chunk
+#[derive(PartialEq)]
+struct ChunkPlan {
+    biome: Biome,
+    // type, position and drift
+    rocks: Vec<(RockType, Vec2, Vec2)>,
+    ores: Vec<(ResourceType, Vec2, Vec2)>,
+    // seed for the debris ring
+    planet: Option<u64>,
+    black_hole: bool,
+    // kind, position and a seed for the drift
+    hazards: Vec<(HazardKind, Vec2, u64)>,
+}
+
+fn plan_chunk(world_mode: WorldMode, seed: u64, coord: IVec2) -> ChunkPlan {
+    let mut rng = chunk_rng(seed, coord);
+    let origin = coord.as_vec2() * CHUNK_SIZE;
+    let mut placed = vec![];
+
+    // pick a biome by weight
+    let total: u32 = Biome::ALL.iter().map(|biome| biome.density().weight).sum();
+    let mut roll = rng.random_range(0..total);
+    let mut biome = Biome::EmptyVoid;
+    for candidate in Biome::ALL {
+        let weight = candidate.density().weight;
+        if roll < weight {
+            biome = candidate;
+            break;
+        }
+        roll -= weight;
+    }
+    let density = biome.density();
+
+    let rock_types = [
+        RockType::Rock1,
+        RockType::Rock2,
+        RockType::Rock3,
+        RockType::Rock4,
+    ];
+
+    let mut rocks = vec![];
+    for _ in 0..density.rocks {
+        let rock_type = rock_types[rng.random_range(0..rock_types.len())];
+        let drift = Vec2::from_angle(rng.random_range(0.0..TAU)) * rng.random_range(0.0..0.15);
+        if let Some(position) = spaced_position(&mut rng, world_mode, origin, &mut placed) {
+            rocks.push((rock_type, position, drift));
+        }
+    }
+
+    // pick each ore by its weight in the mix
+    let mut ores = vec![];
+    let total: f32 = density.ore_mix.iter().map(|(_, weight)| weight).sum();
+    for _ in 0..density.ores {
+        let mut roll = rng.random_range(0.0..total);
+        let mut res_type = density.ore_mix[0].0;
+        for (candidate, weight) in density.ore_mix {
+            if roll < *weight {
+                res_type = *candidate;
+                break;
+            }
+            roll -= weight;
+        }
+
+        let drift = Vec2::from_angle(rng.random_range(0.0..TAU)) * rng.random_range(0.0..0.08);
+        if let Some(position) = spaced_position(&mut rng, world_mode, origin, &mut placed) {
+            ores.push((res_type, position, drift));
+        }
+    }
+
+    let mut planet = None;
+    let mut black_hole = false;
+    if rng.random_bool(density.planet_chance) {
+        planet = Some(rng.random());
+    } else {
+        black_hole = rng.random_bool(density.black_hole_chance);
+    }
+
+    let mut hazards = vec![];
+    for (kind, chance) in density.hazards {
+        if !rng.random_bool(*chance) {
+            continue;
+        }
+        let position = origin
+            + Vec2::new(
+                rng.random_range(0.0..CHUNK_SIZE),
+                rng.random_range(0.0..CHUNK_SIZE),
+            );
+        hazards.push((*kind, position, rng.random()));
+    }
+
+    ChunkPlan {
+        biome,
+        rocks,
+        ores,
+        planet,
+        black_hole,
+        hazards,
+    }
+}
+
+// spawn the chunk's plan, leaving out whatever would land in the exclusion zones
+// the zones never change what the chunk contains, only what gets spawned this time
+fn generate_chunk(
+    commands: &mut Commands,
+    game_textures: &GameTextures,
+    world_mode: WorldMode,
+    seed: u64,
+    coord: IVec2,
+    zones: &[(Vec2, f32)],
+) -> Biome {
+    let plan = plan_chunk(world_mode, seed, coord);
+    let clear = |position: Vec2, radius: f32| {
+        in_world(world_mode, position)
+            && zones.iter().all(|(zone, zone_radius)| {
+                spawn_distance(world_mode, position, *zone) > zone_radius + radius
+            })
+    };
+
+    for (rock_type, position, drift) in plan.rocks {
+        if !clear(position, 0.0) {
+            continue;
+        }
+        let rock = spawn_rock(
+            commands,
+            game_textures,
+            rock_type,
+            position.extend(1.0),
+            Velocity {
+                x: drift.x,
+                y: drift.y,
+            },
+        );
+        commands.entity(rock).insert(Chunk(coord)).insert(Movable {
+            auto_despawn: false,
+        });
+    }
+
+    for (res_type, position, drift) in plan.ores {
+        if !clear(position, 0.0) {
+            continue;
+        }
+        if let Some(ore) = spawn_ore(
+            commands,
+            game_textures,
+            res_type,
+            position.extend(1.0),
+            Velocity {
+                x: drift.x,
+                y: drift.y,
+            },
+        ) {
+            commands.entity(ore).insert(Chunk(coord)).insert(Movable {
+                auto_despawn: false,
+            });
+        }
+    }
+
+    // planets and black holes keep their pull away from the ship and base
+    let center = coord.as_vec2() * CHUNK_SIZE + Vec2::splat(CHUNK_SIZE / 2.0);
+    if let Some(debris_seed) = plan.planet
+        && clear(center, PLANET_WELL.radius)
+    {
+        let mut debris_rng = StdRng::seed_from_u64(debris_seed);
+        spawn_planet(commands, game_textures, &mut debris_rng, center, coord);
+    }
+    if plan.black_hole && clear(center, BLACK_HOLE_WELL.radius) {
+        spawn_black_hole(commands, center, coord);
+    }
+
+    for (kind, position, drift_seed) in plan.hazards {
+        if clear(position, kind.radius()) {
+            let mut drift_rng = StdRng::seed_from_u64(drift_seed);
+            spawn_hazard(commands, &mut drift_rng, kind, position, coord);
+        }
+    }
+
+    plan.biome
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_gives_same_chunk() {
+        for coord in [IVec2::ZERO, IVec2::new(3, -2), IVec2::new(-7, 11)] {
+            let first = plan_chunk(WorldMode::Endless, 42, coord);
+            let second = plan_chunk(WorldMode::Endless, 42, coord);
+            assert!(first == second);
+        }
+    }
+
+    #[test]
+    fn different_seeds_give_different_chunks() {
+        let first = plan_chunk(WorldMode::Endless, 1, IVec2::ZERO);
+        let second = plan_chunk(WorldMode::Endless, 2, IVec2::ZERO);
+        assert!(first != second);
+    }
+
+    #[test]
+    fn spawns_are_spaced_across_chunk_borders() {
+        let mut positions = vec![];
+        for x in -1..=1 {
+            for y in -1..=1 {
+                let plan = plan_chunk(WorldMode::Endless, 7, IVec2::new(x, y));
+                positions.extend(plan.rocks.iter().map(|(_, position, _)| *position));
+                positions.extend(plan.ores.iter().map(|(_, position, _)| *position));
+            }
+        }
+        for (index, a) in positions.iter().enumerate() {
+            for b in &positions[index + 1..] {
+                assert!(a.distance(*b) > MIN_SPAWN_SPACING);
+            }
+        }
+    }
+
+    #[test]
+    fn spawns_stay_in_their_chunk() {
+        let coord = IVec2::new(-2, 5);
+        let origin = coord.as_vec2() * CHUNK_SIZE;
+        let plan = plan_chunk(WorldMode::Endless, 9, coord);
+        for (_, position, _) in &plan.rocks {
+            assert_eq!(chunk_coord(*position), coord);
+            assert!(position.x - origin.x >= MIN_SPAWN_SPACING / 2.0);
+            assert!(position.y - origin.y >= MIN_SPAWN_SPACING / 2.0);
+        }
+    }
+
+    #[test]
+    fn wrapping_world_reuses_chunks() {
+        let world_chunks = (Vec2::from(WORLD_SIZE) / CHUNK_SIZE).as_ivec2();
+        let coord = IVec2::new(1, 2);
+        assert_eq!(
+            chunk_key(WorldMode::Wrap, coord + world_chunks),
+            chunk_key(WorldMode::Wrap, coord)
+        );
+        assert_eq!(
+            chunk_key(WorldMode::Endless, coord + world_chunks),
+            coord + world_chunks
+        );
+    }
+}
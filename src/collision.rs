@@ -0,0 +1,301 @@
+use bevy::{platform::collections::HashMap, prelude::*};
+
+use crate::{SPATIAL_CELL_SIZE, components::Collider};
+
+// grid of everything with a collider, rebuilt every step
+// so collision checks only look at things in the same cells
+#[derive(Resource, Default)]
+pub struct SpatialHash(HashMap<IVec2, Vec<Entity>>);
+
+impl SpatialHash {
+    fn cells(position: Vec2, radius: f32) -> impl Iterator<Item = IVec2> {
+        let min = ((position - radius) / SPATIAL_CELL_SIZE).floor().as_ivec2();
+        let max = ((position + radius) / SPATIAL_CELL_SIZE).floor().as_ivec2();
+        (min.x..=max.x).flat_map(move |x| (min.y..=max.y).map(move |y| IVec2::new(x, y)))
+    }
+
+    // everything sharing a cell with the circle, each entity once
+    pub fn nearby(&self, position: Vec2, radius: f32) -> Vec<Entity> {
+        let mut found = vec![];
+        for cell in Self::cells(position, radius) {
+            if let Some(entities) = self.0.get(&cell) {
+                found.extend(entities);
+            }
+        }
+        found.sort();
+        found.dedup();
+        found
+    }
+}
+
+pub struct CollisionPlugin;
+impl Plugin for CollisionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpatialHash>()
+            .add_systems(FixedPreUpdate, rebuild_spatial_hash);
+    }
+}
+
+fn rebuild_spatial_hash(
+    mut spatial_hash: ResMut<SpatialHash>,
+    query: Query<(Entity, &Transform, &Collider)>,
+) {
+    spatial_hash.0.clear();
+    for (entity, transform, collider) in query {
+        let radius = bounding_radius(collider, transform);
+        for cell in SpatialHash::cells(transform.translation.truncate(), radius) {
+            spatial_hash.0.entry(cell).or_default().push(entity);
+        }
+    }
+}
+
+// circle around the whole collider
+pub fn bounding_radius(collider: &Collider, transform: &Transform) -> f32 {
+    let scale = transform.scale.truncate().max_element();
+    match collider {
+        Collider::Circle(radius) => radius * scale,
+        Collider::Box(half) => half.length() * scale,
+        Collider::Polygon(points) => {
+            points
+                .iter()
+                .map(|point| point.length())
+                .fold(0.0, f32::max)
+                * scale
+        }
+    }
+}
+
+// a collider placed in the world
+enum Shape {
+    Circle(Vec2, f32),
+    Polygon(Vec<Vec2>),
+}
+
+fn world_shape(collider: &Collider, transform: &Transform) -> Shape {
+    let center = transform.translation.truncate();
+    let scale = transform.scale.truncate();
+    let to_world =
+        |point: Vec2| center + (transform.rotation * (point * scale).extend(0.0)).truncate();
+
+    match collider {
+        Collider::Circle(radius) => Shape::Circle(center, radius * scale.max_element()),
+        Collider::Box(half) => Shape::Polygon(vec![
+            to_world(Vec2::new(-half.x, -half.y)),
+            to_world(Vec2::new(half.x, -half.y)),
+            to_world(Vec2::new(half.x, half.y)),
+            to_world(Vec2::new(-half.x, half.y)),
+        ]),
+        Collider::Polygon(points) => {
+            Shape::Polygon(points.iter().map(|point| to_world(*point)).collect())
+        }
+    }
+}
+
+fn shape_center(shape: &Shape) -> Vec2 {
+    match shape {
+        Shape::Circle(center, _) => *center,
+        Shape::Polygon(points) => points.iter().sum::<Vec2>() / points.len().max(1) as f32,
+    }
+}
+
+// lowest and highest point of a shape along an axis
+fn project(shape: &Shape, axis: Vec2) -> (f32, f32) {
+    match shape {
+        Shape::Circle(center, radius) => {
+            let middle = center.dot(axis);
+            (middle - radius, middle + radius)
+        }
+        Shape::Polygon(points) => points
+            .iter()
+            .fold((f32::MAX, f32::MIN), |(min, max), point| {
+                let distance = point.dot(axis);
+                (min.min(distance), max.max(distance))
+            }),
+    }
+}
+
+fn edge_normals(points: &[Vec2]) -> Vec<Vec2> {
+    (0..points.len())
+        .map(|i| {
+            (points[(i + 1) % points.len()] - points[i])
+                .perp()
+                .normalize_or_zero()
+        })
+        .collect()
+}
+
+// direction from a to b and how far they overlap, None when they don't touch
+// separating axis test over the edges of both shapes
+pub fn collide(
+    a: &Collider,
+    a_tf: &Transform,
+    b: &Collider,
+    b_tf: &Transform,
+) -> Option<(Vec2, f32)> {
+    let a = world_shape(a, a_tf);
+    let b = world_shape(b, b_tf);
+
+    let axes = match (&a, &b) {
+        (Shape::Circle(a_center, a_radius), Shape::Circle(b_center, b_radius)) => {
+            let offset = *b_center - *a_center;
+            let depth = a_radius + b_radius - offset.length();
+            if depth <= 0.0 {
+                return None;
+            }
+            return Some((offset.try_normalize().unwrap_or(Vec2::Y), depth));
+        }
+        (Shape::Circle(center, _), Shape::Polygon(points))
+        | (Shape::Polygon(points), Shape::Circle(center, _)) => {
+            let mut axes = edge_normals(points);
+            // the axis towards the closest corner catches hits on corners
+            if let Some(closest) = points.iter().min_by(|p, q| {
+                p.distance_squared(*center)
+                    .total_cmp(&q.distance_squared(*center))
+            }) {
+                axes.push((*center - *closest).normalize_or_zero());
+            }
+            axes
+        }
+        (Shape::Polygon(a_points), Shape::Polygon(b_points)) => {
+            let mut axes = edge_normals(a_points);
+            axes.extend(edge_normals(b_points));
+            axes
+        }
+    };
+
+    let offset = shape_center(&b) - shape_center(&a);
+    let mut contact: Option<(Vec2, f32)> = None;
+    for axis in axes {
+        if axis == Vec2::ZERO {
+            continue;
+        }
+        let (a_min, a_max) = project(&a, axis);
+        let (b_min, b_max) = project(&b, axis);
+        let depth = a_max.min(b_max) - a_min.max(b_min);
+        if depth <= 0.0 {
+            return None;
+        }
+        if contact.is_none_or(|(_, best)| depth < best) {
+            let normal = if offset.dot(axis) < 0.0 { -axis } else { axis };
+            contact = Some((normal, depth));
+        }
+    }
+    contact
+}
+
+pub fn overlaps(a: &Collider, a_tf: &Transform, b: &Collider, b_tf: &Transform) -> bool {
+    collide(a, a_tf, b, b_tf).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_4;
+
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::{PLAYER_COLLIDER, SPATIAL_CELL_SIZE};
+
+    fn at(x: f32, y: f32) -> Transform {
+        Transform::from_xyz(x, y, 0.0)
+    }
+
+    #[test]
+    fn circles_push_apart_along_their_centers() {
+        let circle = Collider::Circle(5.0);
+        let (normal, depth) = collide(&circle, &at(0.0, 0.0), &circle, &at(8.0, 0.0)).unwrap();
+        assert!(normal.abs_diff_eq(Vec2::X, 1e-5));
+        assert!((depth - 2.0).abs() < 1e-5);
+
+        assert!(!overlaps(&circle, &at(0.0, 0.0), &circle, &at(10.0, 0.0)));
+    }
+
+    #[test]
+    fn boxes_separate_along_the_shallowest_axis() {
+        let wide = Collider::Box(Vec2::new(10.0, 5.0));
+        let (normal, depth) = collide(&wide, &at(0.0, 0.0), &wide, &at(4.0, 9.0)).unwrap();
+        assert!(normal.abs_diff_eq(Vec2::Y, 1e-5));
+        assert!((depth - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn rotated_box_misses_where_its_bounds_would_hit() {
+        let square = Collider::Box(Vec2::splat(5.0));
+        let turned = at(0.0, 0.0).with_rotation(Quat::from_rotation_z(FRAC_PI_4));
+        // inside the turned box's bounding square but past its diagonal edge
+        assert!(!overlaps(&square, &turned, &square, &at(12.0, 12.0)));
+        assert!(overlaps(&square, &turned, &square, &at(11.0, 0.0)));
+    }
+
+    #[test]
+    fn circle_misses_box_corner() {
+        let square = Collider::Box(Vec2::splat(5.0));
+        let circle = Collider::Circle(2.0);
+        // the corner is at (5, 5), the circle is about 2.8 away from it
+        assert!(!overlaps(&square, &at(0.0, 0.0), &circle, &at(7.0, 7.0)));
+        assert!(overlaps(&square, &at(0.0, 0.0), &circle, &at(6.0, 6.0)));
+    }
+
+    #[test]
+    fn colliders_scale_with_the_transform() {
+        let circle = Collider::Circle(3.0);
+        let scaled = at(0.0, 0.0).with_scale(Vec3::new(2.0, 2.0, 1.0));
+        assert!(overlaps(&circle, &scaled, &circle, &at(8.0, 0.0)));
+        assert!(!overlaps(&circle, &at(0.0, 0.0), &circle, &at(8.0, 0.0)));
+    }
+
+    #[test]
+    fn ship_polygon_hits_from_the_nose_only_when_close() {
+        let ore = Collider::Circle(1.0);
+        // the nose sticks out to 6.5
+        assert!(overlaps(
+            &PLAYER_COLLIDER,
+            &at(0.0, 0.0),
+            &ore,
+            &at(0.0, 7.0)
+        ));
+        assert!(!overlaps(
+            &PLAYER_COLLIDER,
+            &at(0.0, 0.0),
+            &ore,
+            &at(0.0, 8.0)
+        ));
+        // the sides of the nose slope away
+        assert!(!overlaps(
+            &PLAYER_COLLIDER,
+            &at(0.0, 0.0),
+            &ore,
+            &at(5.0, 5.0)
+        ));
+    }
+
+    #[test]
+    fn cells_cover_the_whole_circle() {
+        let cells: Vec<IVec2> = SpatialHash::cells(Vec2::ZERO, 1.0).collect();
+        assert_eq!(cells.len(), 4);
+        let cells: Vec<IVec2> =
+            SpatialHash::cells(Vec2::splat(SPATIAL_CELL_SIZE / 2.0), 1.0).collect();
+        assert_eq!(cells, vec![IVec2::ZERO]);
+    }
+
+    #[test]
+    fn nearby_finds_each_entity_once() {
+        let mut world = World::new();
+        world.init_resource::<SpatialHash>();
+        // big enough to sit in several cells
+        let big = world
+            .spawn((at(0.0, 0.0), Collider::Circle(SPATIAL_CELL_SIZE)))
+            .id();
+        let close = world.spawn((at(10.0, 10.0), Collider::Circle(2.0))).id();
+        let far = world
+            .spawn((at(SPATIAL_CELL_SIZE * 10.0, 0.0), Collider::Circle(2.0)))
+            .id();
+        world.run_system_once(rebuild_spatial_hash).unwrap();
+
+        let spatial_hash = world.resource::<SpatialHash>();
+        let found = spatial_hash.nearby(Vec2::new(10.0, 10.0), SPATIAL_CELL_SIZE);
+        assert_eq!(found.iter().filter(|entity| **entity == big).count(), 1);
+        assert!(found.contains(&close));
+        assert!(!found.contains(&far));
+    }
+}
@@ -1,9 +1,11 @@
 use bevy::{
-    math::Vec2,
+    math::{IVec2, Quat, Vec2, Vec3},
     prelude::Component,
     time::{Timer, TimerMode},
 };
 
+use crate::trades::ResourceType;
+
 #[derive(Component)]
 pub struct Player;
 
@@ -13,6 +15,17 @@ pub struct Base;
 #[derive(Component)]
 pub struct Rock;
 
+#[derive(Component, Clone, Copy, PartialEq)]
+pub enum RockType {
+    Rock1,
+    Rock2,
+    Rock3,
+    Rock4,
+}
+
+#[derive(Component)]
+pub struct Heat(pub f32);
+
 #[derive(Component)]
 pub struct Gold;
 
@@ -28,6 +41,10 @@ pub struct Coal;
 #[derive(Component)]
 pub struct Stats;
 
+// everything on screen that shows the player's stats
+#[derive(Component)]
+pub struct Hud;
+
 #[derive(Component)]
 pub struct GoldCount;
 
@@ -40,6 +57,12 @@ pub struct CopperCount;
 #[derive(Component)]
 pub struct CoalCount;
 
+#[derive(Component)]
+pub struct SteelCount;
+
+#[derive(Component)]
+pub struct WiringCount;
+
 #[derive(Component)]
 pub struct ShipStorageUi;
 
@@ -49,12 +72,76 @@ pub struct BaseStorageUi;
 #[derive(Component)]
 pub struct PlayerCashUi;
 
+#[derive(Component)]
+pub struct FuelUi;
+
+#[derive(Component)]
+pub struct HullUi;
+
 #[derive(Component)]
 pub struct Velocity {
     pub x: f32,
     pub y: f32,
 }
 
+#[derive(Component)]
+pub struct Fuel(pub f32);
+
+#[derive(Component)]
+pub struct Hull {
+    pub health: f32,
+    pub cooldown: Timer,
+}
+
+impl Hull {
+    pub fn new(health: f32) -> Self {
+        Self {
+            health,
+            cooldown: Timer::from_seconds(0.5, TimerMode::Once),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct TractorBeam {
+    pub level: u32,
+}
+
+// guns mounted on the ship, one hardpoint per level
+#[derive(Component)]
+pub struct Weapon {
+    pub level: u32,
+    pub cooldown: Timer,
+}
+
+#[derive(Component)]
+pub struct Projectile {
+    pub damage: f32,
+    pub lifetime: Timer,
+}
+
+#[derive(Component)]
+pub struct Acceleration {
+    pub x: f32,
+    pub y: f32,
+}
+
+// flight model tuning for a ship class
+#[derive(Component, Clone, Copy)]
+pub struct ShipParams {
+    pub thrust: f32,
+    pub drag: f32,
+    pub max_speed: f32,
+    pub turn_rate: f32,
+}
+
+// last two simulated positions and rotations, used for smooth rendering
+#[derive(Component)]
+pub struct Interpolated {
+    pub previous: (Vec3, Quat),
+    pub current: (Vec3, Quat),
+}
+
 #[derive(Component)]
 pub struct Movable {
     pub auto_despawn: bool,
@@ -85,3 +172,78 @@ impl Default for MaintenanceTimer {
         Self(Timer::from_seconds(30.0, TimerMode::Repeating))
     }
 }
+
+#[derive(Component)]
+pub struct Refinery {
+    pub recipe: Option<usize>,
+    pub timer: Timer,
+}
+
+impl Default for Refinery {
+    fn default() -> Self {
+        Self {
+            recipe: None,
+            timer: Timer::from_seconds(0.0, TimerMode::Once),
+        }
+    }
+}
+
+// the world chunk that generated this entity
+#[derive(Component)]
+pub struct Chunk(pub IVec2);
+
+// collision shape in sprite pixels, scaled and rotated with the transform
+// boxes are given as half sizes, polygons must be convex
+#[derive(Component, Clone, Copy)]
+pub enum Collider {
+    Circle(f32),
+    Box(Vec2),
+    Polygon(&'static [Vec2]),
+}
+
+// pulls the ship, rocks and ores towards it
+#[derive(Component, Clone, Copy)]
+pub struct GravityWell {
+    pub strength: f32,
+    pub radius: f32,
+    pub core: f32,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum HazardKind {
+    // hides the hud
+    Nebula,
+    // damages the hull
+    Radiation,
+    // scrambles the controls
+    IonStorm,
+}
+
+#[derive(Component)]
+pub struct Hazard {
+    pub kind: HazardKind,
+    pub radius: f32,
+}
+
+// hostile ship, carries whatever it stole
+#[derive(Component, Default)]
+pub struct Pirate {
+    pub cargo: Vec<ResourceType>,
+}
+
+// rival miner, sells its cargo at the base
+// gives up and docks for good after wandering around without finding ore
+#[derive(Component)]
+pub struct Trader {
+    pub cargo: Vec<ResourceType>,
+    pub wander: Timer,
+}
+
+// bought from the base, mines one kind of ore on its own
+#[derive(Component)]
+pub struct Drone {
+    pub target: ResourceType,
+    pub cargo: u32,
+    pub upkeep: Timer,
+    pub lifetime: Timer,
+}
@@ -1,81 +1,33 @@
-use bevy::{
-    math::bounding::{Aabb2d, IntersectsVolume},
-    prelude::*,
-};
+use bevy::prelude::*;
 
 use crate::{
-    GOLD_SIZE, GameTextures, MAX_COPPER, SPRITE_SCALE, WinSize,
-    components::{Base, Copper, Movable, SpriteSize, Velocity},
+    BASE_DESPAWN_COLLIDER,
+    collision::{SpatialHash, bounding_radius, overlaps},
+    components::{Base, Collider, Copper},
 };
 
 pub struct CopperPlugin;
 impl Plugin for CopperPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, (copper_movement, copper_spawn));
+        app.add_systems(FixedUpdate, copper_movement);
     }
 }
 
-fn copper_spawn(
-    mut commands: Commands,
-    game_textures: Res<GameTextures>,
-    win_size: Res<WinSize>,
-    copper_query: Query<&Copper>,
-) {
-    if copper_query.iter().len() >= MAX_COPPER {
-        return;
-    }
-
-    use rand::Rng;
-    let mut rng = rand::rng();
-    let offset = 0.0;
-    let w_span = win_size.w / 2.0 + offset;
-    let h_span = win_size.h / 2.0 + offset;
-    let x = rng.random_range(-w_span..w_span);
-    let y = rng.random_range(-h_span..h_span);
-    let z = 1.0;
-    let target_position = Vec3::new(x, y, z);
-
-    commands
-        .spawn((
-            Sprite::from_image(game_textures.copper.clone()),
-            Transform {
-                translation: target_position,
-                scale: Vec3::new(SPRITE_SCALE, SPRITE_SCALE, 1.),
-                ..Default::default()
-            },
-        ))
-        .insert(Copper)
-        .insert(Movable { auto_despawn: true })
-        .insert(Velocity {
-            x: x / 10000.0,
-            y: y / 10000.0,
-        })
-        .insert(SpriteSize::from(GOLD_SIZE));
-}
-
 // move copper slowly
 // despawn when colliding with base
 fn copper_movement(
     mut commands: Commands,
-    base_query: Query<(&Transform, &SpriteSize), With<Base>>,
-    copper_query: Query<(Entity, &Transform, &SpriteSize), With<Copper>>,
+    spatial_hash: Res<SpatialHash>,
+    base_query: Query<&Transform, With<Base>>,
+    copper_query: Query<(Entity, &Transform, &Collider), With<Copper>>,
 ) {
-    if let Ok((base_tf, base_size)) = base_query.single() {
-        let base_scale = Vec2::from(base_tf.scale.xy());
-
-        for (copper, copper_tf, copper_size) in copper_query {
-            let copper_scale = Vec2::from(copper_tf.scale.xy());
-
-            let collision = Aabb2d::new(
-                base_tf.translation.truncate(),
-                base_size.0 * 2.0 * base_scale,
-            )
-            .intersects(&Aabb2d::new(
-                copper_tf.translation.truncate(),
-                copper_size.0 * copper_scale,
-            ));
-
-            if collision {
+    if let Ok(base_tf) = base_query.single() {
+        let nearby = spatial_hash.nearby(
+            base_tf.translation.truncate(),
+            bounding_radius(&BASE_DESPAWN_COLLIDER, base_tf),
+        );
+        for (copper, copper_tf, copper_collider) in copper_query.iter_many(nearby) {
+            if overlaps(&BASE_DESPAWN_COLLIDER, base_tf, copper_collider, copper_tf) {
                 commands.entity(copper).despawn();
             }
         }
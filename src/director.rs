@@ -0,0 +1,260 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    DENSE_BELT_ROCKS, DESPAWN_DISTANCE, DIFFICULTY_PER_CASH, DIFFICULTY_PER_MINUTE,
+    EDGE_SPAWN_MARGIN, GameOver, GameTextures, MAX_DIFFICULTY, METEOR_SHOWER_ROCKS, PlayerCash,
+    ROAMING_ROCKS, ROAMING_ROCKS_PER_DIFFICULTY, ROCK_SPEED_PER_DIFFICULTY, WAVE_INTERVAL, WinSize,
+    camera::CameraZoom,
+    components::{Base, Chunk, Player, Rock, RockType, Velocity},
+    rock::{clear_of_base, screen_edge, spawn_rock},
+};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Wave {
+    // lots of small fast rocks from one side
+    MeteorShower,
+    // a wide band of big slow rocks crossing the screen
+    DenseBelt,
+}
+
+struct ActiveWave {
+    wave: Wave,
+    side: u32,
+    remaining: u32,
+    spawn_timer: Timer,
+}
+
+// ramps up the roaming rocks over time and with the player's cash
+// and every so often sends a wave through
+#[derive(Resource)]
+pub struct Director {
+    pub difficulty: f32,
+    elapsed: f32,
+    wave_timer: Timer,
+    active: Option<ActiveWave>,
+}
+
+impl Default for Director {
+    fn default() -> Self {
+        Self {
+            difficulty: 0.0,
+            elapsed: 0.0,
+            wave_timer: Timer::from_seconds(WAVE_INTERVAL, TimerMode::Repeating),
+            active: None,
+        }
+    }
+}
+
+pub struct DirectorPlugin;
+impl Plugin for DirectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Director>().add_systems(
+            FixedUpdate,
+            (update_difficulty, roaming_spawn, wave_spawn).chain(),
+        );
+    }
+}
+
+// bigger rocks show up more often as difficulty goes up
+fn pick_rock(rng: &mut impl Rng, difficulty: f32) -> RockType {
+    let weights = [
+        (RockType::Rock1, 1.0 + difficulty * 0.3),
+        (RockType::Rock2, 0.5 + difficulty * 0.4),
+        (RockType::Rock3, 1.0),
+        (RockType::Rock4, (3.0 - difficulty * 0.2).max(0.5)),
+    ];
+    let total: f32 = weights.iter().map(|(_, weight)| weight).sum();
+    let mut roll = rng.random_range(0.0..total);
+    for (rock_type, weight) in weights {
+        if roll < weight {
+            return rock_type;
+        }
+        roll -= weight;
+    }
+    RockType::Rock4
+}
+
+fn half_view(win_size: &WinSize, zoom: &CameraZoom) -> Vec2 {
+    Vec2::new(win_size.w, win_size.h) * zoom.0 / 2.0 + EDGE_SPAWN_MARGIN
+}
+
+fn update_difficulty(
+    time: Res<Time>,
+    cash: Res<PlayerCash>,
+    game_over: Res<GameOver>,
+    mut director: ResMut<Director>,
+) {
+    if game_over.0 {
+        return;
+    }
+    director.elapsed += time.delta_secs();
+    director.difficulty = (director.elapsed / 60.0 * DIFFICULTY_PER_MINUTE
+        + cash.0 as f32 * DIFFICULTY_PER_CASH)
+        .min(MAX_DIFFICULTY);
+}
+
+// keep a few rocks drifting through the screen
+// on top of the ones the chunks start with
+fn roaming_spawn(
+    mut commands: Commands,
+    game_textures: Res<GameTextures>,
+    director: Res<Director>,
+    game_over: Res<GameOver>,
+    win_size: Res<WinSize>,
+    zoom: Res<CameraZoom>,
+    player_query: Query<&Transform, With<Player>>,
+    base_query: Query<&Transform, With<Base>>,
+    rock_query: Query<(), (With<Rock>, Without<Chunk>)>,
+) {
+    if game_over.0 {
+        return;
+    }
+    let wanted = ROAMING_ROCKS + (director.difficulty * ROAMING_ROCKS_PER_DIFFICULTY) as usize;
+    if rock_query.iter().len() >= wanted {
+        return;
+    }
+    let Ok(player_tf) = player_query.single() else {
+        return;
+    };
+    let player_pos = player_tf.translation.truncate();
+
+    let mut rng = rand::rng();
+    let half_view = half_view(&win_size, &zoom);
+    let offset = screen_edge(
+        rng.random_range(0..4),
+        rng.random_range(-1.0..1.0),
+        half_view,
+    )
+    .clamp_length_max(DESPAWN_DISTANCE - EDGE_SPAWN_MARGIN);
+    if !clear_of_base(&base_query, player_pos + offset) {
+        return;
+    }
+
+    // drift somewhere across the screen, faster as difficulty goes up
+    let target = player_pos
+        + Vec2::new(
+            rng.random_range(-0.5..0.5) * half_view.x,
+            rng.random_range(-0.5..0.5) * half_view.y,
+        );
+    let speed =
+        rng.random_range(0.1..0.3) * (1.0 + director.difficulty * ROCK_SPEED_PER_DIFFICULTY);
+    let drift = (target - player_pos - offset).normalize_or_zero() * speed;
+
+    spawn_rock(
+        &mut commands,
+        &game_textures,
+        pick_rock(&mut rng, director.difficulty),
+        (player_pos + offset).extend(1.0),
+        Velocity {
+            x: drift.x,
+            y: drift.y,
+        },
+    );
+}
+
+fn wave_spawn(
+    mut commands: Commands,
+    game_textures: Res<GameTextures>,
+    time: Res<Time>,
+    game_over: Res<GameOver>,
+    win_size: Res<WinSize>,
+    zoom: Res<CameraZoom>,
+    mut director: ResMut<Director>,
+    player_query: Query<&Transform, With<Player>>,
+    base_query: Query<&Transform, With<Base>>,
+) {
+    if game_over.0 {
+        return;
+    }
+    let mut rng = rand::rng();
+    let difficulty = director.difficulty;
+
+    // start a new wave
+    if director.wave_timer.tick(time.delta()).just_finished() && director.active.is_none() {
+        let wave = if rng.random_bool(0.5) {
+            Wave::MeteorShower
+        } else {
+            Wave::DenseBelt
+        };
+        let (count, interval) = match wave {
+            Wave::MeteorShower => (METEOR_SHOWER_ROCKS, 0.1),
+            Wave::DenseBelt => (DENSE_BELT_ROCKS, 0.05),
+        };
+        director.active = Some(ActiveWave {
+            wave,
+            side: rng.random_range(0..4),
+            remaining: count + (difficulty * 2.0) as u32,
+            spawn_timer: Timer::from_seconds(interval, TimerMode::Repeating),
+        });
+    }
+
+    let Some(active) = director.active.as_mut() else {
+        return;
+    };
+    if !active.spawn_timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    let Ok(player_tf) = player_query.single() else {
+        return;
+    };
+    let player_pos = player_tf.translation.truncate();
+
+    let half_view = half_view(&win_size, &zoom);
+    let speed_scale = 1.0 + difficulty * ROCK_SPEED_PER_DIFFICULTY;
+    let offset = screen_edge(active.side, rng.random_range(-1.0..1.0), half_view)
+        .clamp_length_max(DESPAWN_DISTANCE - EDGE_SPAWN_MARGIN);
+    // straight across to the opposite side
+    let across = match active.side {
+        0 => Vec2::X,
+        1 => Vec2::NEG_X,
+        2 => Vec2::Y,
+        _ => Vec2::NEG_Y,
+    };
+    // the slot is skipped so the wave still ends on time
+    let clear = clear_of_base(&base_query, player_pos + offset);
+
+    let (rock_type, velocity) = match active.wave {
+        Wave::MeteorShower => {
+            let spread = Vec2::from_angle(rng.random_range(-0.2..0.2));
+            let rock_type = if rng.random_bool(0.7) {
+                RockType::Rock4
+            } else {
+                RockType::Rock3
+            };
+            (
+                rock_type,
+                spread.rotate(across) * rng.random_range(0.8..1.2) * speed_scale,
+            )
+        }
+        Wave::DenseBelt => {
+            let wobble = Vec2::from_angle(rng.random_range(0.0..TAU)) * 0.02;
+            let rock_type = if rng.random_bool(0.5) {
+                RockType::Rock1
+            } else {
+                RockType::Rock2
+            };
+            (rock_type, across * 0.3 * speed_scale + wobble)
+        }
+    };
+
+    if clear {
+        spawn_rock(
+            &mut commands,
+            &game_textures,
+            rock_type,
+            (player_pos + offset).extend(1.0),
+            Velocity {
+                x: velocity.x,
+                y: velocity.y,
+            },
+        );
+    }
+
+    active.remaining -= 1;
+    if active.remaining == 0 {
+        director.active = None;
+    }
+}
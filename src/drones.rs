@@ -0,0 +1,290 @@
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+
+use crate::{
+    BASE_DOCK_COLLIDER, BaseStorage, DOCK_RANGE, DRONE_CAPACITY, DRONE_COST, DRONE_HULL,
+    DRONE_LIFETIME, DRONE_SCALE, DRONE_SHIP, DRONE_SIGHT, DRONE_UPKEEP, DRONE_UPKEEP_INTERVAL,
+    GameOver, GameTextures, MAX_BASE_STORAGE, MAX_DRONES, PLAYER_COLLIDER, PlayerCash,
+    collision::{SpatialHash, bounding_radius, overlaps},
+    components::{Base, Coal, Collider, Copper, Drone, Gold, Hull, Iron, Movable, Player},
+    mining::{ore_type, spill_ore},
+    steering::{Behavior, spawn_ai_ship, steer},
+    traders::trader_collect,
+    trades::ResourceType,
+};
+
+use thousands::Separable;
+
+// the ore new drones are sent after
+#[derive(Resource)]
+struct DroneOrder(ResourceType);
+
+pub struct DronePlugin;
+impl Plugin for DronePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(DroneOrder(ResourceType::Iron))
+            .add_systems(
+                FixedUpdate,
+                (
+                    drone_behavior.before(steer),
+                    drone_collect.after(trader_collect),
+                    drone_unload,
+                    drone_upkeep,
+                    drone_destroyed,
+                ),
+            )
+            .add_systems(EguiPrimaryContextPass, generate_drone_bay);
+    }
+}
+
+fn spawn_drone(
+    commands: &mut Commands,
+    game_textures: &GameTextures,
+    target: ResourceType,
+    position: Vec3,
+) {
+    spawn_ai_ship(
+        commands,
+        game_textures,
+        DRONE_SHIP,
+        DRONE_HULL,
+        Color::srgb(0.5, 0.7, 1.0),
+        position.with_z(5.0),
+        DRONE_SCALE,
+    )
+    .insert(Drone {
+        target,
+        cargo: 0,
+        upkeep: Timer::from_seconds(DRONE_UPKEEP_INTERVAL, TimerMode::Repeating),
+        lifetime: Timer::from_seconds(DRONE_LIFETIME, TimerMode::Once),
+    })
+    .insert(Behavior::Idle)
+    .insert(Movable {
+        auto_despawn: false,
+    });
+}
+
+// go for the nearest ore of the drone's type until the hold is full
+// then return to the base, wait there when there's nothing to mine
+fn drone_behavior(
+    base_query: Query<&Transform, With<Base>>,
+    ore_query: Query<
+        (&Transform, Has<Gold>, Has<Iron>, Has<Copper>),
+        Or<(With<Gold>, With<Iron>, With<Copper>, With<Coal>)>,
+    >,
+    mut drone_query: Query<(&Transform, &Drone, &mut Behavior)>,
+) {
+    let Ok(base_tf) = base_query.single() else {
+        return;
+    };
+    let base_pos = base_tf.translation.truncate();
+
+    for (drone_tf, drone, mut behavior) in drone_query.iter_mut() {
+        let position = drone_tf.translation.truncate();
+
+        let mut nearest = DRONE_SIGHT;
+        let mut target = None;
+        for (ore_tf, is_gold, is_iron, is_copper) in ore_query {
+            if ore_type(is_gold, is_iron, is_copper) != drone.target {
+                continue;
+            }
+            let distance = ore_tf.translation.truncate().distance(position);
+            if distance < nearest {
+                nearest = distance;
+                target = Some(ore_tf.translation.truncate());
+            }
+        }
+
+        let next = if drone.cargo >= DRONE_CAPACITY {
+            Behavior::Arrive(base_pos)
+        } else if let Some(target) = target {
+            Behavior::Arrive(target)
+        } else if drone.cargo > 0
+            || !overlaps(&BASE_DOCK_COLLIDER, base_tf, &PLAYER_COLLIDER, drone_tf)
+        {
+            Behavior::Arrive(base_pos)
+        } else {
+            Behavior::Idle
+        };
+        behavior.set_if_neq(next);
+    }
+}
+
+// runs after the player and traders so an ore is only ever collected once
+fn drone_collect(
+    mut commands: Commands,
+    spatial_hash: Res<SpatialHash>,
+    mut drone_query: Query<(&Transform, &Collider, &mut Drone)>,
+    ore_query: Query<
+        (
+            Entity,
+            &Transform,
+            &Collider,
+            Has<Gold>,
+            Has<Iron>,
+            Has<Copper>,
+        ),
+        Or<(With<Gold>, With<Iron>, With<Copper>, With<Coal>)>,
+    >,
+) {
+    let mut collected = vec![];
+
+    for (drone_tf, drone_collider, mut drone) in drone_query.iter_mut() {
+        if drone.cargo >= DRONE_CAPACITY {
+            continue;
+        }
+        let nearby = spatial_hash.nearby(
+            drone_tf.translation.truncate(),
+            bounding_radius(drone_collider, drone_tf),
+        );
+        for (ore, ore_tf, ore_collider, is_gold, is_iron, is_copper) in ore_query.iter_many(&nearby)
+        {
+            if collected.contains(&ore)
+                || ore_type(is_gold, is_iron, is_copper) != drone.target
+                || !overlaps(drone_collider, drone_tf, ore_collider, ore_tf)
+            {
+                continue;
+            }
+            commands.entity(ore).despawn();
+            collected.push(ore);
+            drone.cargo += 1;
+            if drone.cargo >= DRONE_CAPACITY {
+                break;
+            }
+        }
+    }
+}
+
+// drones unload as much as fits into base storage
+// whatever doesn't fit stays in the hold until there's room
+fn drone_unload(
+    mut base_storage: ResMut<BaseStorage>,
+    base_query: Query<&Transform, With<Base>>,
+    mut drone_query: Query<(&Transform, &Collider, &mut Drone)>,
+) {
+    let Ok(base_tf) = base_query.single() else {
+        return;
+    };
+
+    for (drone_tf, drone_collider, mut drone) in drone_query.iter_mut() {
+        if drone.cargo == 0 || !overlaps(drone_collider, drone_tf, &BASE_DOCK_COLLIDER, base_tf) {
+            continue;
+        }
+
+        // the base total has to stay below the cap, same as the player's unload
+        let room = MAX_BASE_STORAGE.saturating_sub(base_storage.total() + 1);
+        let unloaded = drone.cargo.min(room);
+        *base_storage.amount_mut(drone.target) += unloaded;
+        drone.cargo -= unloaded;
+    }
+}
+
+// destroyed drones spill what they were carrying
+fn drone_destroyed(
+    mut commands: Commands,
+    game_textures: Res<GameTextures>,
+    drone_query: Query<(Entity, &Transform, &Drone, &Hull)>,
+) {
+    for (entity, drone_tf, drone, hull) in drone_query {
+        if hull.health > 0.0 {
+            continue;
+        }
+        commands.entity(entity).despawn();
+        spill_ore(
+            &mut commands,
+            &game_textures,
+            (0..drone.cargo).map(|_| drone.target),
+            drone_tf.translation,
+        );
+    }
+}
+
+// drones cost credits to run and wear out after a while
+// a drone that can't be paid for is scrapped
+fn drone_upkeep(
+    mut commands: Commands,
+    time: Res<Time>,
+    game_over: Res<GameOver>,
+    mut player_cash: ResMut<PlayerCash>,
+    mut drone_query: Query<(Entity, &mut Drone)>,
+) {
+    if game_over.0 {
+        return;
+    }
+    for (entity, mut drone) in drone_query.iter_mut() {
+        if drone.lifetime.tick(time.delta()).just_finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        if drone.upkeep.tick(time.delta()).just_finished() {
+            if player_cash.0 >= DRONE_UPKEEP {
+                player_cash.0 -= DRONE_UPKEEP;
+            } else {
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+}
+
+fn generate_drone_bay(
+    mut contexts: EguiContexts,
+    mut commands: Commands,
+    game_textures: Res<GameTextures>,
+    mut cash: ResMut<PlayerCash>,
+    mut order: ResMut<DroneOrder>,
+    base: Single<&Transform, With<Base>>,
+    player: Single<&Transform, With<Player>>,
+    drone_query: Query<&Drone>,
+    game_over: Res<GameOver>,
+) -> Result {
+    if game_over.0 {
+        return Ok(());
+    }
+    // drones are bought at the base, so the bay only opens while docked there
+    if player.translation.distance(base.translation) >= DOCK_RANGE {
+        return Ok(());
+    }
+    egui::Window::new("Drone Bay").show(contexts.ctx_mut()?, |ui| {
+        // which ore new drones go after
+        ui.horizontal(|ui| {
+            for res_type in [
+                ResourceType::Gold,
+                ResourceType::Iron,
+                ResourceType::Copper,
+                ResourceType::Coal,
+            ] {
+                ui.radio_value(&mut order.0, res_type, res_type.name());
+            }
+        });
+
+        ui.horizontal(|ui| {
+            let drones = drone_query.iter().len();
+            if ui
+                .add_enabled(drones < MAX_DRONES, egui::Button::new("Buy"))
+                .clicked()
+                && cash.0 >= DRONE_COST
+            {
+                cash.0 -= DRONE_COST;
+                spawn_drone(&mut commands, &game_textures, order.0, base.translation);
+            }
+            ui.label(format!("Drones {}/{}", drones, MAX_DRONES));
+            ui.label(format!("{}", DRONE_COST.separate_with_commas()));
+            ui.label("Galactic Credits");
+        });
+        ui.label(format!(
+            "Upkeep {} every {}s, lasts {}s",
+            DRONE_UPKEEP, DRONE_UPKEEP_INTERVAL, DRONE_LIFETIME
+        ));
+        ui.separator();
+
+        // each drone's ore, hold and remaining life
+        for drone in drone_query {
+            ui.horizontal(|ui| {
+                ui.label(drone.target.name());
+                ui.label(format!("{}/{}", drone.cargo, DRONE_CAPACITY));
+                ui.add(egui::ProgressBar::new(1.0 - drone.lifetime.fraction()).show_percentage());
+            });
+        }
+    });
+    Ok(())
+}
@@ -0,0 +1,166 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use crate::{
+    BASE_SPEED, BASE_WELL, BLACK_HOLE_WELL, CORE_DAMAGE, GameTextures, PLANET_DEBRIS, PLANET_WELL,
+    components::{
+        Base, Chunk, Coal, Copper, Gold, GravityWell, Hazard, Hull, Iron, Movable, Rock, RockType,
+        Velocity,
+    },
+    rock::spawn_rock,
+};
+
+// the base pulls things in gently
+#[derive(Resource, Default)]
+pub struct BaseGravity(pub bool);
+
+pub struct GravityPlugin;
+impl Plugin for GravityPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BaseGravity>()
+            .add_systems(FixedUpdate, (base_gravity, apply_gravity, core_damage))
+            .add_systems(Update, draw_wells);
+    }
+}
+
+// pull towards the well, stronger the closer you get
+// at 100 pixels away the pull is the well's strength
+fn pull(well: &GravityWell, offset: Vec2) -> Vec2 {
+    let distance = offset.length();
+    if distance > well.radius || distance == 0.0 {
+        return Vec2::ZERO;
+    }
+    let distance = distance.max(well.core).max(10.0);
+    offset / offset.length() * well.strength / (distance / 100.0).powi(2)
+}
+
+// speed for a circular orbit at this distance, in velocity units
+fn orbit_speed(well: &GravityWell, distance: f32) -> f32 {
+    (well.strength * 10000.0 / (distance * BASE_SPEED)).sqrt()
+}
+
+pub fn spawn_planet(
+    commands: &mut Commands,
+    game_textures: &GameTextures,
+    rng: &mut impl rand::Rng,
+    position: Vec2,
+    chunk: IVec2,
+) {
+    commands.spawn((
+        Transform::from_translation(position.extend(0.0)),
+        PLANET_WELL,
+        Chunk(chunk),
+    ));
+
+    // a ring of debris in orbit around it
+    let rocks = [RockType::Rock3, RockType::Rock4];
+    for _ in 0..PLANET_DEBRIS {
+        let angle = rng.random_range(0.0..TAU);
+        let distance = rng.random_range(PLANET_WELL.core * 2.0..PLANET_WELL.radius * 0.5);
+        let direction = Vec2::from_angle(angle);
+        let speed = orbit_speed(&PLANET_WELL, distance);
+        let rock = spawn_rock(
+            commands,
+            game_textures,
+            rocks[rng.random_range(0..rocks.len())],
+            (position + direction * distance).extend(1.0),
+            Velocity {
+                x: -direction.y * speed,
+                y: direction.x * speed,
+            },
+        );
+        commands.entity(rock).insert(Chunk(chunk)).insert(Movable {
+            auto_despawn: false,
+        });
+    }
+}
+
+pub fn spawn_black_hole(commands: &mut Commands, position: Vec2, chunk: IVec2) {
+    commands.spawn((
+        Transform::from_translation(position.extend(0.0)),
+        BLACK_HOLE_WELL,
+        Chunk(chunk),
+    ));
+}
+
+fn base_gravity(
+    mut commands: Commands,
+    base_gravity: Res<BaseGravity>,
+    base_query: Query<(Entity, Has<GravityWell>), With<Base>>,
+) {
+    for (base, has_well) in base_query {
+        if base_gravity.0 && !has_well {
+            commands.entity(base).insert(BASE_WELL);
+        } else if !base_gravity.0 && has_well {
+            commands.entity(base).remove::<GravityWell>();
+        }
+    }
+}
+
+fn apply_gravity(
+    time: Res<Time>,
+    well_query: Query<(&Transform, &GravityWell)>,
+    mut query: Query<
+        (&Transform, &mut Velocity),
+        (With<Movable>, Without<GravityWell>, Without<Hazard>),
+    >,
+) {
+    let delta = time.delta_secs();
+    for (well_tf, well) in well_query {
+        let well_pos = well_tf.translation.truncate();
+        for (transform, mut velocity) in query.iter_mut() {
+            let pull = pull(well, well_pos - transform.translation.truncate());
+            velocity.x += pull.x * delta;
+            velocity.y += pull.y * delta;
+        }
+    }
+}
+
+// rocks and ores falling into a core are gone, ships burn up slowly
+fn core_damage(
+    mut commands: Commands,
+    time: Res<Time>,
+    well_query: Query<(&Transform, &GravityWell)>,
+    mut ship_query: Query<(&Transform, &mut Hull)>,
+    debris_query: Query<
+        (Entity, &Transform),
+        Or<(With<Rock>, With<Gold>, With<Iron>, With<Copper>, With<Coal>)>,
+    >,
+) {
+    for (well_tf, well) in well_query {
+        let well_pos = well_tf.translation.truncate();
+
+        for (debris, debris_tf) in debris_query {
+            if debris_tf.translation.truncate().distance(well_pos) < well.core {
+                commands.entity(debris).despawn();
+            }
+        }
+
+        for (ship_tf, mut hull) in ship_query.iter_mut() {
+            if ship_tf.translation.truncate().distance(well_pos) < well.core {
+                hull.health -= CORE_DAMAGE * time.delta_secs();
+            }
+        }
+    }
+}
+
+fn draw_wells(mut gizmos: Gizmos, well_query: Query<(&Transform, &GravityWell)>) {
+    for (well_tf, well) in well_query {
+        let well_pos = well_tf.translation.truncate();
+        if well.core <= 0.0 {
+            continue;
+        }
+
+        // black holes are small and dark, planets big and blue
+        let color = if well.strength >= BLACK_HOLE_WELL.strength {
+            Color::srgb(0.5, 0.2, 0.8)
+        } else {
+            Color::srgb(0.3, 0.6, 0.9)
+        };
+        for ring in 1..=4 {
+            gizmos.circle_2d(well_pos, well.core * ring as f32 / 4.0, color);
+        }
+        gizmos.circle_2d(well_pos, well.radius, color.with_alpha(0.08));
+    }
+}
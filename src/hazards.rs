@@ -0,0 +1,172 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    HAZARD_DRIFT, ION_SCRAMBLE, ION_STORM_RADIUS, NEBULA_RADIUS, RADIATION_DAMAGE,
+    RADIATION_RADIUS,
+    components::{Chunk, Hazard, HazardKind, Hud, Hull, Movable, Player, Velocity},
+    input::{ShipInput, read_ship_input},
+};
+
+// hazards the ship is inside of right now
+#[derive(Resource, Default)]
+pub struct HazardEffects {
+    pub nebula: bool,
+    pub radiation: bool,
+    pub ion_storm: bool,
+}
+
+pub struct HazardPlugin;
+impl Plugin for HazardPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HazardEffects>()
+            .add_systems(PreUpdate, scramble_controls.after(read_ship_input))
+            .add_systems(FixedUpdate, (check_hazards, radiation_damage).chain())
+            .add_systems(Update, (hide_hud, draw_hazards));
+    }
+}
+
+impl HazardKind {
+    pub fn radius(&self) -> f32 {
+        match self {
+            HazardKind::Nebula => NEBULA_RADIUS,
+            HazardKind::Radiation => RADIATION_RADIUS,
+            HazardKind::IonStorm => ION_STORM_RADIUS,
+        }
+    }
+}
+
+pub fn spawn_hazard(
+    commands: &mut Commands,
+    rng: &mut impl Rng,
+    kind: HazardKind,
+    position: Vec2,
+    chunk: IVec2,
+) {
+    let drift = Vec2::from_angle(rng.random_range(0.0..TAU)) * HAZARD_DRIFT;
+    commands.spawn((
+        Transform::from_translation(position.extend(0.0)),
+        Hazard {
+            kind,
+            radius: kind.radius(),
+        },
+        Movable {
+            auto_despawn: false,
+        },
+        Velocity {
+            x: drift.x,
+            y: drift.y,
+        },
+        Chunk(chunk),
+    ));
+}
+
+fn check_hazards(
+    mut effects: ResMut<HazardEffects>,
+    player_query: Query<&Transform, With<Player>>,
+    hazard_query: Query<(&Transform, &Hazard)>,
+) {
+    *effects = HazardEffects::default();
+    let Ok(player_tf) = player_query.single() else {
+        return;
+    };
+
+    for (hazard_tf, hazard) in hazard_query {
+        let offset = (hazard_tf.translation - player_tf.translation).truncate();
+        if offset.length() > hazard.radius {
+            continue;
+        }
+        match hazard.kind {
+            HazardKind::Nebula => effects.nebula = true,
+            HazardKind::Radiation => effects.radiation = true,
+            HazardKind::IonStorm => effects.ion_storm = true,
+        }
+    }
+}
+
+fn radiation_damage(
+    time: Res<Time>,
+    effects: Res<HazardEffects>,
+    mut player_query: Query<&mut Hull, With<Player>>,
+) {
+    if !effects.radiation {
+        return;
+    }
+    for mut hull in player_query.iter_mut() {
+        hull.health -= RADIATION_DAMAGE * time.delta_secs();
+    }
+}
+
+// twist the aim, flip the turn direction and cut the engine now and then
+fn scramble_controls(effects: Res<HazardEffects>, mut ship_input: ResMut<ShipInput>) {
+    if !effects.ion_storm {
+        return;
+    }
+    let mut rng = rand::rng();
+    if let Some(aim) = ship_input.aim {
+        let twist = Vec2::from_angle(rng.random_range(-ION_SCRAMBLE..ION_SCRAMBLE));
+        ship_input.aim = Some(twist.rotate(aim));
+    }
+    if rng.random_bool(0.3) {
+        ship_input.turn = -ship_input.turn;
+    }
+    if rng.random_bool(0.1) {
+        ship_input.thrust = 0.0;
+    }
+}
+
+fn hide_hud(effects: Res<HazardEffects>, mut hud_query: Query<&mut Visibility, With<Hud>>) {
+    let visibility = if effects.nebula {
+        Visibility::Hidden
+    } else {
+        Visibility::Inherited
+    };
+    for mut hud in hud_query.iter_mut() {
+        hud.set_if_neq(visibility);
+    }
+}
+
+fn draw_hazards(mut gizmos: Gizmos, hazard_query: Query<(&Transform, &Hazard)>) {
+    let mut rng = rand::rng();
+    for (hazard_tf, hazard) in hazard_query {
+        let center = hazard_tf.translation.truncate();
+        match hazard.kind {
+            // a few overlapping clouds
+            HazardKind::Nebula => {
+                let color = Color::srgba(0.8, 0.4, 0.9, 0.25);
+                gizmos.circle_2d(center, hazard.radius, color);
+                gizmos.circle_2d(
+                    center + hazard.radius * Vec2::new(0.3, 0.2),
+                    hazard.radius * 0.6,
+                    color,
+                );
+                gizmos.circle_2d(
+                    center - hazard.radius * Vec2::new(0.25, 0.3),
+                    hazard.radius * 0.5,
+                    color,
+                );
+            }
+            HazardKind::Radiation => {
+                let color = Color::srgba(0.9, 0.9, 0.2, 0.4);
+                gizmos.circle_2d(center, hazard.radius, color);
+                gizmos.circle_2d(center, hazard.radius * 0.95, color);
+            }
+            // flickering lightning inside the storm
+            HazardKind::IonStorm => {
+                let color = Color::srgba(0.3, 0.9, 1.0, 0.4);
+                gizmos.circle_2d(center, hazard.radius, color);
+                let mut point = center
+                    + Vec2::from_angle(rng.random_range(0.0..TAU))
+                        * rng.random_range(0.0..hazard.radius * 0.5);
+                for _ in 0..5 {
+                    let next =
+                        point + Vec2::from_angle(rng.random_range(0.0..TAU)) * hazard.radius * 0.15;
+                    gizmos.line_2d(point, next, Color::srgb(0.6, 1.0, 1.0));
+                    point = next;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use bevy::{
+    input::InputSystems, platform::collections::HashSet, prelude::*, window::PrimaryWindow,
+};
+use bevy_egui::EguiContexts;
+
+use crate::{
+    GAMEPAD_DEADZONE,
+    components::Player,
+    keymap::{Action, Binding, Keymap, Rebinding},
+};
+
+// actions held down and pressed this frame, resolved through the keymap
+#[derive(Resource, Default)]
+pub struct ActionState {
+    pressed: HashSet<Action>,
+    just_pressed: HashSet<Action>,
+}
+
+impl ActionState {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+}
+
+// what the player wants the ship to do this frame
+// filled from keyboard, mouse and gamepads
+#[derive(Resource, Default)]
+pub struct ShipInput {
+    pub turn: f32,
+    pub thrust: f32,
+    pub aim: Option<Vec2>,
+    pub mine: bool,
+    pub fire: bool,
+    pub dock: bool,
+}
+
+#[derive(Resource, Default, Clone, Copy, PartialEq)]
+pub enum ControlMode {
+    #[default]
+    Directional,
+    TurnAndThrust,
+    MouseAim,
+}
+
+pub struct InputPlugin;
+impl Plugin for InputPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShipInput>()
+            .init_resource::<ControlMode>()
+            .init_resource::<ActionState>()
+            .add_systems(
+                PreUpdate,
+                (read_actions, read_ship_input).chain().after(InputSystems),
+            );
+    }
+}
+
+fn read_actions(
+    mut contexts: EguiContexts,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    gamepads: Query<&Gamepad>,
+    keymap: Res<Keymap>,
+    rebinding: Res<Rebinding>,
+    mut actions: ResMut<ActionState>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    actions.pressed.clear();
+    actions.just_pressed.clear();
+
+    // buttons pressed while rebinding belong to the new binding
+    if rebinding.0.is_some() {
+        return;
+    }
+
+    // clicks on the ui windows don't fly or fire the ship
+    let pointer_on_ui = contexts
+        .ctx_mut()
+        .is_ok_and(|ctx| ctx.wants_pointer_input());
+
+    for action in Action::ALL {
+        for binding in keymap.bindings(action) {
+            let (pressed, just_pressed) = match *binding {
+                Binding::Key(key) => (keys.pressed(key), keys.just_pressed(key)),
+                Binding::Mouse(_) if pointer_on_ui => (false, false),
+                Binding::Mouse(button) => (mouse.pressed(button), mouse.just_pressed(button)),
+                Binding::Pad(button) => (
+                    gamepads.iter().any(|gamepad| gamepad.pressed(button)),
+                    gamepads.iter().any(|gamepad| gamepad.just_pressed(button)),
+                ),
+            };
+            if pressed {
+                actions.pressed.insert(action);
+            }
+            if just_pressed {
+                actions.just_pressed.insert(action);
+            }
+        }
+    }
+
+    if actions.just_pressed(Action::Pause) {
+        if time.is_paused() {
+            time.unpause();
+        } else {
+            time.pause();
+        }
+    }
+}
+
+pub fn read_ship_input(
+    actions: Res<ActionState>,
+    time: Res<Time<Virtual>>,
+    mut control_mode: ResMut<ControlMode>,
+    mut ship_input: ResMut<ShipInput>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    player_query: Query<&Transform, With<Player>>,
+    gamepads: Query<&Gamepad>,
+) {
+    *ship_input = ShipInput::default();
+    if time.is_paused() {
+        return;
+    }
+
+    // cycle control modes
+    if actions.just_pressed(Action::CycleControls) {
+        *control_mode = match *control_mode {
+            ControlMode::Directional => ControlMode::TurnAndThrust,
+            ControlMode::TurnAndThrust => ControlMode::MouseAim,
+            ControlMode::MouseAim => ControlMode::Directional,
+        };
+    }
+
+    let left = actions.pressed(Action::TurnLeft);
+    let right = actions.pressed(Action::TurnRight);
+    let up = actions.pressed(Action::Thrust);
+    let down = actions.pressed(Action::Reverse);
+
+    match *control_mode {
+        // face the pressed direction and fly that way
+        ControlMode::Directional => {
+            let mut direction = Vec2::ZERO;
+            if left {
+                direction.x -= 1.0;
+            }
+            if right {
+                direction.x += 1.0;
+            }
+            if up {
+                direction.y += 1.0;
+            }
+            if down {
+                direction.y -= 1.0;
+            }
+            if direction != Vec2::ZERO {
+                ship_input.aim = Some(direction);
+                ship_input.thrust = 1.0;
+            }
+        }
+        // rotate left and right, thrust forward or brake backwards
+        ControlMode::TurnAndThrust => {
+            if left {
+                ship_input.turn += 1.0;
+            }
+            if right {
+                ship_input.turn -= 1.0;
+            }
+            if up {
+                ship_input.thrust = 1.0;
+            } else if down {
+                ship_input.thrust = -0.5;
+            }
+        }
+        // face the cursor and thrust forward
+        ControlMode::MouseAim => {
+            if let (Ok(window), Ok((camera, camera_tf)), Ok(player_tf)) = (
+                window_query.single(),
+                camera_query.single(),
+                player_query.single(),
+            ) && let Some(cursor) = window.cursor_position()
+                && let Ok(target) = camera.viewport_to_world_2d(camera_tf, cursor)
+            {
+                ship_input.aim = Some(target - player_tf.translation.truncate());
+            }
+            if up {
+                ship_input.thrust = 1.0;
+            } else if down {
+                ship_input.thrust = -0.5;
+            }
+        }
+    }
+
+    ship_input.mine = actions.pressed(Action::Mine);
+    ship_input.fire = actions.pressed(Action::Fire);
+    ship_input.dock = actions.pressed(Action::Dock);
+
+    // left stick aims and thrusts in every mode
+    for gamepad in gamepads {
+        let stick = gamepad.left_stick();
+        if stick.length() > GAMEPAD_DEADZONE {
+            ship_input.aim = Some(stick);
+            ship_input.thrust = stick.length().min(1.0);
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use bevy::prelude::*;
+
+use crate::{
+    WorldMode,
+    components::{Interpolated, Movable},
+    wrap_offset,
+};
+
+// gameplay runs in FixedUpdate and owns the real transforms
+// between fixed steps the rendered transform is blended from the last two steps
+pub struct InterpolationPlugin;
+impl Plugin for InterpolationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedFirst, (track_movables, store_previous))
+            .add_systems(FixedLast, store_current)
+            .add_systems(
+                RunFixedMainLoop,
+                (
+                    restore_current.in_set(RunFixedMainLoopSystems::BeforeFixedMainLoop),
+                    interpolate.in_set(RunFixedMainLoopSystems::AfterFixedMainLoop),
+                ),
+            );
+    }
+}
+
+fn track_movables(
+    mut commands: Commands,
+    query: Query<(Entity, &Transform), (With<Movable>, Without<Interpolated>)>,
+) {
+    for (entity, transform) in query {
+        commands.entity(entity).insert(Interpolated {
+            previous: (transform.translation, transform.rotation),
+            current: (transform.translation, transform.rotation),
+        });
+    }
+}
+
+fn store_previous(mut query: Query<(&Transform, &mut Interpolated)>) {
+    for (transform, mut interpolated) in query.iter_mut() {
+        interpolated.previous = (transform.translation, transform.rotation);
+    }
+}
+
+fn store_current(mut query: Query<(&Transform, &mut Interpolated)>) {
+    for (transform, mut interpolated) in query.iter_mut() {
+        interpolated.current = (transform.translation, transform.rotation);
+    }
+}
+
+// put back the simulated transform before the next fixed steps
+fn restore_current(mut query: Query<(&mut Transform, &Interpolated)>) {
+    for (mut transform, interpolated) in query.iter_mut() {
+        transform.translation = interpolated.current.0;
+        transform.rotation = interpolated.current.1;
+    }
+}
+
+fn interpolate(
+    fixed_time: Res<Time<Fixed>>,
+    world_mode: Res<WorldMode>,
+    mut query: Query<(&mut Transform, &Interpolated)>,
+) {
+    let alpha = fixed_time.overstep_fraction();
+    for (mut transform, interpolated) in query.iter_mut() {
+        // blend from the side of the seam the entity is on now
+        let mut previous = interpolated.previous.0;
+        if *world_mode == WorldMode::Wrap {
+            let current = interpolated.current.0;
+            let offset = wrap_offset((current - previous).truncate());
+            previous = current - offset.extend(current.z - previous.z);
+        }
+        transform.translation = previous.lerp(interpolated.current.0, alpha);
+        transform.rotation = interpolated.previous.1.slerp(interpolated.current.1, alpha);
+    }
+}
@@ -1,81 +1,33 @@
-use bevy::{
-    math::bounding::{Aabb2d, IntersectsVolume},
-    prelude::*,
-};
+use bevy::prelude::*;
 
 use crate::{
-    GOLD_SIZE, GameTextures, MAX_IRON, SPRITE_SCALE, WinSize,
-    components::{Base, Iron, Movable, SpriteSize, Velocity},
+    BASE_DESPAWN_COLLIDER,
+    collision::{SpatialHash, bounding_radius, overlaps},
+    components::{Base, Collider, Iron},
 };
 
 pub struct IronPlugin;
 impl Plugin for IronPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, (iron_movement, iron_spawn));
+        app.add_systems(FixedUpdate, iron_movement);
     }
 }
 
-fn iron_spawn(
-    mut commands: Commands,
-    game_textures: Res<GameTextures>,
-    win_size: Res<WinSize>,
-    iron_query: Query<&Iron>,
-) {
-    if iron_query.iter().len() >= MAX_IRON {
-        return;
-    }
-
-    use rand::Rng;
-    let mut rng = rand::rng();
-    let offset = 0.0;
-    let w_span = win_size.w / 2.0 + offset;
-    let h_span = win_size.h / 2.0 + offset;
-    let x = rng.random_range(-w_span..w_span);
-    let y = rng.random_range(-h_span..h_span);
-    let z = 1.0;
-    let target_position = Vec3::new(x, y, z);
-
-    commands
-        .spawn((
-            Sprite::from_image(game_textures.iron.clone()),
-            Transform {
-                translation: target_position,
-                scale: Vec3::new(SPRITE_SCALE, SPRITE_SCALE, 1.),
-                ..Default::default()
-            },
-        ))
-        .insert(Iron)
-        .insert(Movable { auto_despawn: true })
-        .insert(Velocity {
-            x: x / 10000.0,
-            y: y / 10000.0,
-        })
-        .insert(SpriteSize::from(GOLD_SIZE));
-}
-
 // move iron slowly
 // despawn when colliding with base
 fn iron_movement(
     mut commands: Commands,
-    base_query: Query<(&Transform, &SpriteSize), With<Base>>,
-    iron_query: Query<(Entity, &Transform, &SpriteSize), With<Iron>>,
+    spatial_hash: Res<SpatialHash>,
+    base_query: Query<&Transform, With<Base>>,
+    iron_query: Query<(Entity, &Transform, &Collider), With<Iron>>,
 ) {
-    if let Ok((base_tf, base_size)) = base_query.single() {
-        let base_scale = Vec2::from(base_tf.scale.xy());
-
-        for (iron, iron_tf, iron_size) in iron_query {
-            let iron_scale = Vec2::from(iron_tf.scale.xy());
-
-            let collision = Aabb2d::new(
-                base_tf.translation.truncate(),
-                base_size.0 * 2.0 * base_scale,
-            )
-            .intersects(&Aabb2d::new(
-                iron_tf.translation.truncate(),
-                iron_size.0 * iron_scale,
-            ));
-
-            if collision {
+    if let Ok(base_tf) = base_query.single() {
+        let nearby = spatial_hash.nearby(
+            base_tf.translation.truncate(),
+            bounding_radius(&BASE_DESPAWN_COLLIDER, base_tf),
+        );
+        for (iron, iron_tf, iron_collider) in iron_query.iter_many(nearby) {
+            if overlaps(&BASE_DESPAWN_COLLIDER, base_tf, iron_collider, iron_tf) {
                 commands.entity(iron).despawn();
             }
         }
@@ -0,0 +1,493 @@
+use std::{env, fs, path::PathBuf};
+
+use bevy::{
+    platform::collections::HashMap,
+    prelude::*,
+    reflect::{DynamicEnum, DynamicVariant, TypeInfo, Typed, VariantInfo},
+};
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+
+use crate::{WorldMode, gravity::BaseGravity, input::ControlMode, rock::EdgeEntry};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Action {
+    Thrust,
+    Reverse,
+    TurnLeft,
+    TurnRight,
+    Mine,
+    Fire,
+    Dock,
+    Pause,
+    ToggleMarket,
+    CycleControls,
+    ZoomIn,
+    ZoomOut,
+}
+
+impl Action {
+    pub const ALL: [Action; 12] = [
+        Action::Thrust,
+        Action::Reverse,
+        Action::TurnLeft,
+        Action::TurnRight,
+        Action::Mine,
+        Action::Fire,
+        Action::Dock,
+        Action::Pause,
+        Action::ToggleMarket,
+        Action::CycleControls,
+        Action::ZoomIn,
+        Action::ZoomOut,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Thrust => "Thrust",
+            Action::Reverse => "Reverse",
+            Action::TurnLeft => "TurnLeft",
+            Action::TurnRight => "TurnRight",
+            Action::Mine => "Mine",
+            Action::Fire => "Fire",
+            Action::Dock => "Dock",
+            Action::Pause => "Pause",
+            Action::ToggleMarket => "ToggleMarket",
+            Action::CycleControls => "CycleControls",
+            Action::ZoomIn => "ZoomIn",
+            Action::ZoomOut => "ZoomOut",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Pad(GamepadButton),
+}
+
+impl Binding {
+    fn to_line(self) -> String {
+        match self {
+            Binding::Key(key) => format!("Key:{:?}", key),
+            Binding::Mouse(button) => format!("Mouse:{:?}", button),
+            Binding::Pad(button) => format!("Pad:{:?}", button),
+        }
+    }
+
+    fn from_line(line: &str) -> Option<Self> {
+        let (kind, name) = line.split_once(':')?;
+        match kind {
+            "Key" => parse_variant(name).map(Binding::Key),
+            "Mouse" => parse_variant(name).map(Binding::Mouse),
+            "Pad" => parse_variant(name).map(Binding::Pad),
+            _ => None,
+        }
+    }
+}
+
+// look up a unit enum variant like KeyCode::KeyA by its name
+fn parse_variant<T: FromReflect + Typed>(name: &str) -> Option<T> {
+    let TypeInfo::Enum(info) = T::type_info() else {
+        return None;
+    };
+    if !matches!(info.variant(name), Some(VariantInfo::Unit(_))) {
+        return None;
+    }
+    T::from_reflect(&DynamicEnum::new(name, DynamicVariant::Unit))
+}
+
+#[derive(Resource, Clone, Debug)]
+pub struct Keymap(pub HashMap<Action, Vec<Binding>>);
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let mut bindings = HashMap::default();
+        bindings.insert(
+            Action::Thrust,
+            vec![
+                Binding::Key(KeyCode::KeyW),
+                Binding::Key(KeyCode::ArrowUp),
+                Binding::Pad(GamepadButton::RightTrigger2),
+            ],
+        );
+        bindings.insert(
+            Action::Reverse,
+            vec![
+                Binding::Key(KeyCode::KeyS),
+                Binding::Key(KeyCode::ArrowDown),
+                Binding::Pad(GamepadButton::LeftTrigger2),
+            ],
+        );
+        bindings.insert(
+            Action::TurnLeft,
+            vec![
+                Binding::Key(KeyCode::KeyA),
+                Binding::Key(KeyCode::ArrowLeft),
+                Binding::Pad(GamepadButton::DPadLeft),
+            ],
+        );
+        bindings.insert(
+            Action::TurnRight,
+            vec![
+                Binding::Key(KeyCode::KeyD),
+                Binding::Key(KeyCode::ArrowRight),
+                Binding::Pad(GamepadButton::DPadRight),
+            ],
+        );
+        bindings.insert(
+            Action::Mine,
+            vec![
+                Binding::Key(KeyCode::Space),
+                Binding::Mouse(MouseButton::Right),
+                Binding::Pad(GamepadButton::South),
+            ],
+        );
+        bindings.insert(
+            Action::Fire,
+            vec![
+                Binding::Key(KeyCode::KeyF),
+                Binding::Mouse(MouseButton::Left),
+                Binding::Pad(GamepadButton::RightTrigger),
+            ],
+        );
+        bindings.insert(
+            Action::Dock,
+            vec![
+                Binding::Key(KeyCode::KeyE),
+                Binding::Pad(GamepadButton::West),
+            ],
+        );
+        bindings.insert(
+            Action::Pause,
+            vec![
+                Binding::Key(KeyCode::Escape),
+                Binding::Pad(GamepadButton::Start),
+            ],
+        );
+        bindings.insert(
+            Action::ToggleMarket,
+            vec![
+                Binding::Key(KeyCode::KeyM),
+                Binding::Pad(GamepadButton::Select),
+            ],
+        );
+        bindings.insert(
+            Action::CycleControls,
+            vec![
+                Binding::Key(KeyCode::KeyC),
+                Binding::Pad(GamepadButton::North),
+            ],
+        );
+        bindings.insert(
+            Action::ZoomIn,
+            vec![
+                Binding::Key(KeyCode::Equal),
+                Binding::Key(KeyCode::NumpadAdd),
+                Binding::Pad(GamepadButton::DPadUp),
+            ],
+        );
+        bindings.insert(
+            Action::ZoomOut,
+            vec![
+                Binding::Key(KeyCode::Minus),
+                Binding::Key(KeyCode::NumpadSubtract),
+                Binding::Pad(GamepadButton::DPadDown),
+            ],
+        );
+        Keymap(bindings)
+    }
+}
+
+impl Keymap {
+    pub fn bindings(&self, action: Action) -> &[Binding] {
+        self.0.get(&action).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    fn load() -> Self {
+        let Some(path) = keymap_path() else {
+            return Keymap::default();
+        };
+        let Ok(text) = fs::read_to_string(path) else {
+            return Keymap::default();
+        };
+        Keymap::from_text(&text)
+    }
+
+    // one line per action:
+    // Thrust=Key:KeyW,Pad:RightTrigger2
+    // actions missing from the text keep their default bindings
+    fn from_text(text: &str) -> Self {
+        let mut keymap = Keymap::default();
+        for line in text.lines() {
+            let Some((name, bindings)) = line.split_once('=') else {
+                continue;
+            };
+            let Some(action) = Action::ALL
+                .iter()
+                .find(|action| action.name() == name.trim())
+            else {
+                continue;
+            };
+            let bindings = bindings
+                .split(',')
+                .filter_map(|binding| Binding::from_line(binding.trim()))
+                .collect();
+            keymap.0.insert(*action, bindings);
+        }
+        keymap.keep_pause();
+        keymap
+    }
+
+    // the controls are only reachable while paused, so pause can't end up unbound
+    fn keep_pause(&mut self) {
+        let bindings = self.0.entry(Action::Pause).or_default();
+        if bindings.is_empty() {
+            bindings.push(Binding::Key(KeyCode::Escape));
+        }
+    }
+
+    fn to_text(&self) -> String {
+        let mut text = String::new();
+        for action in Action::ALL {
+            let bindings: Vec<String> = self
+                .bindings(action)
+                .iter()
+                .map(|binding| binding.to_line())
+                .collect();
+            text += &format!("{}={}\n", action.name(), bindings.join(","));
+        }
+        text
+    }
+
+    fn save(&self) {
+        let Some(path) = keymap_path() else {
+            return;
+        };
+        let text = self.to_text();
+
+        if let Some(dir) = path.parent()
+            && let Err(err) = fs::create_dir_all(dir)
+        {
+            warn!("could not create config dir: {err}");
+            return;
+        }
+        if let Err(err) = fs::write(&path, text) {
+            warn!("could not save keymap: {err}");
+        }
+    }
+}
+
+fn keymap_path() -> Option<PathBuf> {
+    let config_dir = if cfg!(target_os = "windows") {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+    } else {
+        env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+    };
+    config_dir.map(|dir| dir.join("spacy-trade").join("keymap.txt"))
+}
+
+// action waiting for the next pressed button
+#[derive(Resource, Default)]
+pub struct Rebinding(pub Option<Action>);
+
+pub struct KeymapPlugin;
+impl Plugin for KeymapPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Keymap::load())
+            .init_resource::<Rebinding>()
+            .add_systems(Update, capture_binding)
+            .add_systems(EguiPrimaryContextPass, generate_controls);
+    }
+}
+
+fn capture_binding(
+    mut contexts: EguiContexts,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    gamepads: Query<&Gamepad>,
+    mut rebinding: ResMut<Rebinding>,
+    mut keymap: ResMut<Keymap>,
+) {
+    let Some(action) = rebinding.0 else {
+        return;
+    };
+
+    // escape backs out without binding anything
+    if keys.just_pressed(KeyCode::Escape) {
+        rebinding.0 = None;
+        return;
+    }
+
+    // clicks on the controls window, like the rebind button itself, aren't bindings
+    let pointer_on_ui = contexts
+        .ctx_mut()
+        .is_ok_and(|ctx| ctx.wants_pointer_input());
+
+    let mut binding = keys.get_just_pressed().next().copied().map(Binding::Key);
+    if binding.is_none() && !pointer_on_ui {
+        binding = mouse.get_just_pressed().next().copied().map(Binding::Mouse);
+    }
+    for gamepad in gamepads {
+        if binding.is_none() {
+            binding = gamepad.get_just_pressed().next().copied().map(Binding::Pad);
+        }
+    }
+
+    if let Some(binding) = binding {
+        let bindings = keymap.0.entry(action).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+        keymap.save();
+        rebinding.0 = None;
+    }
+}
+
+// rebinding screen, shown while the game is paused
+fn generate_controls(
+    mut contexts: EguiContexts,
+    mut time: ResMut<Time<Virtual>>,
+    mut keymap: ResMut<Keymap>,
+    mut rebinding: ResMut<Rebinding>,
+    mut control_mode: ResMut<ControlMode>,
+    mut world_mode: ResMut<WorldMode>,
+    mut edge_entry: ResMut<EdgeEntry>,
+    mut base_gravity: ResMut<BaseGravity>,
+) -> Result {
+    if !time.is_paused() {
+        return Ok(());
+    }
+    egui::Window::new("Paused").show(contexts.ctx_mut()?, |ui| {
+        if ui.button("Resume").clicked() {
+            time.unpause();
+        }
+        ui.separator();
+
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut *control_mode, ControlMode::Directional, "Directional");
+            ui.radio_value(
+                &mut *control_mode,
+                ControlMode::TurnAndThrust,
+                "Turn and Thrust",
+            );
+            ui.radio_value(&mut *control_mode, ControlMode::MouseAim, "Mouse Aim");
+        });
+        // changing the world mode regenerates every chunk
+        // so only write it back when a different mode was picked
+        let mut mode = *world_mode;
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut mode, WorldMode::Endless, "Endless");
+            ui.radio_value(&mut mode, WorldMode::Bounded, "Bounded");
+            ui.radio_value(&mut mode, WorldMode::Wrap, "Wrap");
+        });
+        world_mode.set_if_neq(mode);
+        ui.checkbox(&mut edge_entry.0, "Asteroids drift in from the edges");
+        ui.checkbox(&mut base_gravity.0, "The base pulls things in");
+        ui.separator();
+
+        let mut changed = false;
+        for action in Action::ALL {
+            ui.horizontal(|ui| {
+                ui.label(action.name());
+                let bindings: Vec<String> = keymap
+                    .bindings(action)
+                    .iter()
+                    .map(|binding| binding.to_line())
+                    .collect();
+                ui.label(bindings.join(", "));
+
+                if rebinding.0 == Some(action) {
+                    ui.label("press a button, escape to cancel...");
+                } else if ui.button("Add").clicked() {
+                    rebinding.0 = Some(action);
+                }
+                if ui.button("Clear").clicked() {
+                    keymap.0.insert(action, vec![]);
+                    keymap.keep_pause();
+                    changed = true;
+                }
+            });
+        }
+        ui.separator();
+
+        if ui.button("Reset Defaults").clicked() {
+            *keymap = Keymap::default();
+            changed = true;
+        }
+        if changed {
+            keymap.save();
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bindings_round_trip_through_lines() {
+        for binding in [
+            Binding::Key(KeyCode::KeyW),
+            Binding::Key(KeyCode::ArrowUp),
+            Binding::Mouse(MouseButton::Right),
+            Binding::Pad(GamepadButton::RightTrigger2),
+        ] {
+            assert_eq!(Binding::from_line(&binding.to_line()), Some(binding));
+        }
+    }
+
+    #[test]
+    fn bad_lines_are_ignored() {
+        assert_eq!(Binding::from_line("Key:NotAKey"), None);
+        assert_eq!(Binding::from_line("Joystick:KeyW"), None);
+        assert_eq!(Binding::from_line("KeyW"), None);
+        // only unit variants can be bound
+        assert_eq!(Binding::from_line("Mouse:Other"), None);
+    }
+
+    #[test]
+    fn keymap_round_trips_through_text() {
+        let mut keymap = Keymap::default();
+        keymap.0.insert(
+            Action::Fire,
+            vec![
+                Binding::Key(KeyCode::KeyJ),
+                Binding::Pad(GamepadButton::East),
+            ],
+        );
+        keymap.0.insert(Action::Dock, vec![]);
+
+        let loaded = Keymap::from_text(&keymap.to_text());
+        for action in Action::ALL {
+            assert_eq!(loaded.bindings(action), keymap.bindings(action));
+        }
+    }
+
+    #[test]
+    fn missing_actions_keep_their_defaults() {
+        let loaded = Keymap::from_text("Thrust=Key:KeyI\nNotAnAction=Key:KeyK\n");
+        assert_eq!(
+            loaded.bindings(Action::Thrust),
+            [Binding::Key(KeyCode::KeyI)]
+        );
+        assert_eq!(
+            loaded.bindings(Action::Mine),
+            Keymap::default().bindings(Action::Mine)
+        );
+    }
+
+    #[test]
+    fn pause_always_keeps_a_binding() {
+        let loaded = Keymap::from_text("Pause=\n");
+        assert_eq!(
+            loaded.bindings(Action::Pause),
+            [Binding::Key(KeyCode::Escape)]
+        );
+    }
+}
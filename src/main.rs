@@ -2,17 +2,36 @@
 
 use bevy::{prelude::*, window::PrimaryWindow};
 use components::*;
+use trades::ResourceType;
 
 mod base;
+mod camera;
+mod chunks;
 mod coal;
+mod collision;
 mod components;
 mod copper;
+mod director;
+mod drones;
 mod gold;
+mod gravity;
+mod hazards;
+mod input;
+mod interpolation;
 mod iron;
+mod keymap;
+mod mining;
+mod pirates;
 mod player;
+mod refinery;
 mod rock;
+mod shipyard;
 mod stats;
+mod steering;
+mod tractor;
+mod traders;
 mod trades;
+mod weapons;
 
 const SOUND_TRACK: &str = "sound-track.mp3";
 
@@ -36,18 +55,293 @@ const ROCK2_SIZE: (f32, f32) = (16.0, 8.0);
 const ROCK3_SIZE: (f32, f32) = (11.0, 9.0);
 const ROCK4_SIZE: (f32, f32) = (6.0, 5.0);
 
+const PLAYER_COLLIDER: Collider = Collider::Polygon(&[
+    Vec2::new(0.0, 6.5),
+    Vec2::new(7.0, -2.0),
+    Vec2::new(5.0, -6.5),
+    Vec2::new(-5.0, -6.5),
+    Vec2::new(-7.0, -2.0),
+]);
+const BASE_COLLIDER: Collider = Collider::Box(Vec2::new(15.0, 10.0));
+// things drifting in here get cleaned up, twice the base size on each side
+const BASE_DESPAWN_COLLIDER: Collider = Collider::Box(Vec2::new(60.0, 40.0));
+// the ship gets pushed out of the base itself, so touching it counts as docked
+const BASE_DOCK_COLLIDER: Collider = Collider::Box(Vec2::new(17.0, 12.0));
+const ORE_COLLIDER: Collider = Collider::Circle(3.0);
+const ROCK1_COLLIDER: Collider = Collider::Circle(5.0);
+const ROCK2_COLLIDER: Collider = Collider::Box(Vec2::new(8.0, 4.0));
+const ROCK3_COLLIDER: Collider = Collider::Circle(5.0);
+const ROCK4_COLLIDER: Collider = Collider::Circle(2.75);
+
+// heat needed to break a rock, the rocks it splits into and its ore drop chances
+struct RockYield {
+    heat: f32,
+    fragments: &'static [RockType],
+    loot: &'static [(ResourceType, f64)],
+}
+
+const ROCK1_YIELD: RockYield = RockYield {
+    heat: 2.0,
+    fragments: &[RockType::Rock4, RockType::Rock4],
+    loot: &[(ResourceType::Iron, 0.6), (ResourceType::Copper, 0.3)],
+};
+const ROCK2_YIELD: RockYield = RockYield {
+    heat: 3.0,
+    fragments: &[RockType::Rock4, RockType::Rock4, RockType::Rock4],
+    loot: &[(ResourceType::Copper, 0.5), (ResourceType::Iron, 0.5)],
+};
+const ROCK3_YIELD: RockYield = RockYield {
+    heat: 2.5,
+    fragments: &[RockType::Rock4, RockType::Rock4],
+    loot: &[(ResourceType::Coal, 0.6), (ResourceType::Gold, 0.05)],
+};
+const ROCK4_YIELD: RockYield = RockYield {
+    heat: 1.0,
+    fragments: &[],
+    loot: &[(ResourceType::Coal, 0.3), (ResourceType::Gold, 0.05)],
+};
+
+// what the refinery can craft, inputs are taken from base storage
+struct Recipe {
+    name: &'static str,
+    inputs: &'static [(ResourceType, u32)],
+    output: (ResourceType, u32),
+    seconds: f32,
+}
+
+const RECIPES: &[Recipe] = &[
+    Recipe {
+        name: "Steel",
+        inputs: &[(ResourceType::Iron, 1), (ResourceType::Coal, 1)],
+        output: (ResourceType::Steel, 1),
+        seconds: 5.0,
+    },
+    Recipe {
+        name: "Wiring",
+        inputs: &[(ResourceType::Copper, 1), (ResourceType::Steel, 1)],
+        output: (ResourceType::Wiring, 1),
+        seconds: 8.0,
+    },
+];
+
 const SPRITE_SCALE: f32 = 2.0;
 const BASE_SPEED: f32 = 100.0;
 
-const MAX_ROCKS: usize = 70;
-const MAX_GOLD: usize = 1;
-const MAX_IRON: usize = 3;
-const MAX_COPPER: usize = 3;
-const MAX_COAL: usize = 3;
+const WORLD_SIZE: (f32, f32) = (4000.0, 4000.0);
+const DESPAWN_DISTANCE: f32 = 1200.0;
+
+const CHUNK_SIZE: f32 = 800.0;
+const CHUNK_LOAD_RADIUS: i32 = 2;
+
+const SHIP_CLEAR_RADIUS: f32 = 200.0;
+const BASE_CLEAR_RADIUS: f32 = 250.0;
+const MIN_SPAWN_SPACING: f32 = 24.0;
+const SPAWN_ATTEMPTS: usize = 8;
+const EDGE_SPAWN_CHANCE: f64 = 0.01;
+const EDGE_SPAWN_MARGIN: f32 = 40.0;
+
+const DIFFICULTY_PER_MINUTE: f32 = 0.5;
+const DIFFICULTY_PER_CASH: f32 = 1.0 / 5000.0;
+const MAX_DIFFICULTY: f32 = 10.0;
+const ROAMING_ROCKS: usize = 10;
+const ROAMING_ROCKS_PER_DIFFICULTY: f32 = 4.0;
+const ROCK_SPEED_PER_DIFFICULTY: f32 = 0.1;
+const WAVE_INTERVAL: f32 = 60.0;
+const METEOR_SHOWER_ROCKS: u32 = 30;
+const DENSE_BELT_ROCKS: u32 = 40;
+
+const CAMERA_FOLLOW_RATE: f32 = 5.0;
+const CAMERA_ZOOM: f32 = 1.0;
+const MIN_CAMERA_ZOOM: f32 = 0.5;
+const MAX_CAMERA_ZOOM: f32 = 3.0;
+const CAMERA_ZOOM_SPEED: f32 = 1.5;
+
+const GAMEPAD_DEADZONE: f32 = 0.2;
+const DOCK_RANGE: f32 = 80.0;
+
+const PLAYER_SHIP: ShipParams = ShipParams {
+    thrust: 3.0,
+    drag: 0.8,
+    max_speed: 2.0,
+    turn_rate: 5.0,
+};
+
+// how often a biome is picked, how many rocks and ores a chunk gets,
+// the ore mix as relative weights, the chance of a planet or black hole
+// and the chance of each hazard
+struct BiomeDensity {
+    weight: u32,
+    rocks: usize,
+    ores: usize,
+    ore_mix: &'static [(ResourceType, f32)],
+    planet_chance: f64,
+    black_hole_chance: f64,
+    hazards: &'static [(HazardKind, f64)],
+}
+
+const ASTEROID_BELT: BiomeDensity = BiomeDensity {
+    weight: 3,
+    rocks: 40,
+    ores: 4,
+    ore_mix: &[
+        (ResourceType::Iron, 1.0),
+        (ResourceType::Copper, 1.0),
+        (ResourceType::Coal, 1.0),
+    ],
+    planet_chance: 0.15,
+    black_hole_chance: 0.0,
+    hazards: &[(HazardKind::Radiation, 0.1), (HazardKind::IonStorm, 0.05)],
+};
+const ORE_FIELD: BiomeDensity = BiomeDensity {
+    weight: 2,
+    rocks: 10,
+    ores: 14,
+    ore_mix: &[
+        (ResourceType::Iron, 1.0),
+        (ResourceType::Copper, 1.0),
+        (ResourceType::Coal, 1.0),
+        (ResourceType::Gold, 0.1),
+    ],
+    planet_chance: 0.1,
+    black_hole_chance: 0.0,
+    hazards: &[(HazardKind::Nebula, 0.15)],
+};
+const EMPTY_VOID: BiomeDensity = BiomeDensity {
+    weight: 2,
+    rocks: 2,
+    ores: 0,
+    ore_mix: &[],
+    planet_chance: 0.05,
+    black_hole_chance: 0.1,
+    hazards: &[(HazardKind::Nebula, 0.1), (HazardKind::IonStorm, 0.1)],
+};
+
+const MINING_RANGE: f32 = 150.0;
+const MINING_HEAT_RATE: f32 = 1.0;
+const ROCK_COOL_RATE: f32 = 0.5;
+
+const ROCK_RESTITUTION: f32 = 0.8;
+const ROCK_SPLIT_SPEED: f32 = 0.4;
+
+const SPATIAL_CELL_SIZE: f32 = 64.0;
+
+// pull at 100 pixels away, how far the pull reaches
+// and the core that swallows debris and burns the ship
+const PLANET_WELL: GravityWell = GravityWell {
+    strength: 1.5,
+    radius: 600.0,
+    core: 60.0,
+};
+const BLACK_HOLE_WELL: GravityWell = GravityWell {
+    strength: 4.0,
+    radius: 900.0,
+    core: 20.0,
+};
+const BASE_WELL: GravityWell = GravityWell {
+    strength: 0.2,
+    radius: 250.0,
+    core: 0.0,
+};
+const PLANET_DEBRIS: usize = 12;
+const CORE_DAMAGE: f32 = 20.0;
+
+const NEBULA_RADIUS: f32 = 300.0;
+const RADIATION_RADIUS: f32 = 200.0;
+const ION_STORM_RADIUS: f32 = 250.0;
+const HAZARD_DRIFT: f32 = 0.05;
+const RADIATION_DAMAGE: f32 = 3.0;
+// how far an ion storm can twist the aim, in radians
+const ION_SCRAMBLE: f32 = 1.2;
+
+const AVOID_DISTANCE: f32 = 60.0;
+const AVOID_WEIGHT: f32 = 1.5;
+const ARRIVE_DISTANCE: f32 = 100.0;
+
+const PIRATE_SHIP: ShipParams = ShipParams {
+    thrust: 2.5,
+    drag: 0.8,
+    max_speed: 1.8,
+    turn_rate: 4.0,
+};
+const PIRATE_HULL: f32 = 40.0;
+const PIRATE_FLEE_HEALTH: f32 = 15.0;
+const PIRATE_SIGHT: f32 = 700.0;
+const PIRATE_SPAWN_INTERVAL: f32 = 15.0;
+// the first pirate shows up about two minutes in, more follow as difficulty goes up
+const PIRATE_FIRST_DIFFICULTY: f32 = 1.0;
+const PIRATES_PER_DIFFICULTY: f32 = 0.5;
+// a pirate carrying loot this far from the ship got away with it
+const PIRATE_ESCAPE_DISTANCE: f32 = 2000.0;
+
+const TRADER_SHIP: ShipParams = ShipParams {
+    thrust: 2.0,
+    drag: 0.8,
+    max_speed: 1.4,
+    turn_rate: 3.5,
+};
+const MAX_TRADERS: usize = 3;
+const TRADER_SPAWN_INTERVAL: f32 = 30.0;
+const TRADER_CAPACITY: usize = 5;
+const TRADER_SIGHT: f32 = 500.0;
+const TRADER_HULL: f32 = 30.0;
+// seconds a trader wanders without ore in sight before heading home
+const TRADER_WANDER_TIME: f32 = 40.0;
+// each unit a trader sells drops the price by 1/50
+const TRADER_PRICE_DROP: u32 = 50;
+
+const DRONE_SHIP: ShipParams = ShipParams {
+    thrust: 2.5,
+    drag: 1.0,
+    max_speed: 1.5,
+    turn_rate: 5.0,
+};
+const DRONE_SCALE: f32 = 1.2;
+const DRONE_COST: u32 = 2500;
+const MAX_DRONES: usize = 4;
+const DRONE_CAPACITY: u32 = 3;
+const DRONE_SIGHT: f32 = 800.0;
+const DRONE_HULL: f32 = 20.0;
+const DRONE_UPKEEP: u32 = 50;
+const DRONE_UPKEEP_INTERVAL: f32 = 20.0;
+const DRONE_LIFETIME: f32 = 300.0;
+
+const SHIP_MASS: f32 = 200.0;
+const SHIP_RESTITUTION: f32 = 0.5;
+
+const TRACTOR_RADIUS: f32 = 40.0;
+const TRACTOR_RADIUS_PER_LEVEL: f32 = 20.0;
+const TRACTOR_PULL_SPEED: f32 = 120.0;
+const TRACTOR_UPGRADE_COST: u32 = 1500;
+const MAX_TRACTOR_LEVEL: u32 = 5;
+
+const WEAPON_COOLDOWN: f32 = 0.3;
+const WEAPON_UPGRADE_COST: u32 = 2000;
+const MAX_WEAPON_LEVEL: u32 = 4;
+const HARDPOINT_SPREAD: f32 = 0.12;
+const PROJECTILE_SPEED: f32 = 6.0;
+const PROJECTILE_LIFETIME: f32 = 1.5;
+const PROJECTILE_DAMAGE: f32 = 10.0;
+const PROJECTILE_COLLIDER: Collider = Collider::Circle(1.5);
+// rocks break the same way they do under the mining laser
+const ROCK_HEAT_PER_DAMAGE: f32 = 0.08;
 
 const MAX_SHIP_STORAGE: u32 = 10;
 const MAX_BASE_STORAGE: u32 = 100;
 
+const MAX_FUEL: f32 = 100.0;
+const FUEL_BURN_RATE: f32 = 2.0;
+const FUEL_PER_COAL: f32 = 25.0;
+const EMPTY_TANK_SPEED: f32 = 0.2;
+
+const MAX_HULL: f32 = 100.0;
+const HULL_DAMAGE: f32 = 15.0;
+const REPAIR_COST: u32 = 20;
+const RESPAWN_PENALTY: u32 = 1000;
+// right under the base
+const PLAYER_SPAWN: Vec3 = Vec3::new(0., -40.0, 10.);
+// how far past the tractor beam lost cargo lands
+const CARGO_DROP_DISTANCE: f32 = 24.0;
+
 #[derive(Resource)]
 pub struct WinSize {
     pub w: f32,
@@ -83,6 +377,51 @@ struct BaseStorage {
     iron: u32,
     copper: u32,
     coal: u32,
+    steel: u32,
+    wiring: u32,
+}
+
+impl BaseStorage {
+    fn amount(&self, res_type: ResourceType) -> u32 {
+        match res_type {
+            ResourceType::Gold => self.gold,
+            ResourceType::Iron => self.iron,
+            ResourceType::Copper => self.copper,
+            ResourceType::Coal => self.coal,
+            ResourceType::Steel => self.steel,
+            ResourceType::Wiring => self.wiring,
+        }
+    }
+
+    fn total(&self) -> u32 {
+        let mut base_total = self.gold;
+        base_total += self.iron;
+        base_total += self.copper;
+        base_total += self.coal;
+        base_total += self.steel;
+        base_total += self.wiring;
+        base_total
+    }
+
+    fn amount_mut(&mut self, res_type: ResourceType) -> &mut u32 {
+        match res_type {
+            ResourceType::Gold => &mut self.gold,
+            ResourceType::Iron => &mut self.iron,
+            ResourceType::Copper => &mut self.copper,
+            ResourceType::Coal => &mut self.coal,
+            ResourceType::Steel => &mut self.steel,
+            ResourceType::Wiring => &mut self.wiring,
+        }
+    }
+}
+
+// endless chunks, the walled in world or a world that wraps around at its edges
+#[derive(Resource, Default, Clone, Copy, PartialEq)]
+enum WorldMode {
+    #[default]
+    Endless,
+    Bounded,
+    Wrap,
 }
 
 #[derive(Resource)]
@@ -96,6 +435,7 @@ fn main() {
         .insert_resource(ClearColor(Color::srgb(0.04, 0.04, 0.04)))
         .insert_resource(GameOver(false))
         .insert_resource(PlayerCash(500))
+        .init_resource::<WorldMode>()
         .insert_resource(ShipStorage {
             gold: 0,
             iron: 0,
@@ -107,6 +447,8 @@ fn main() {
             iron: 0,
             copper: 0,
             coal: 0,
+            steel: 0,
+            wiring: 0,
         })
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
@@ -115,17 +457,36 @@ fn main() {
             }),
             ..Default::default()
         }))
+        .add_plugins(interpolation::InterpolationPlugin)
+        .add_plugins(collision::CollisionPlugin)
+        .add_plugins(input::InputPlugin)
+        .add_plugins(keymap::KeymapPlugin)
         .add_plugins(player::PlayerPlugin)
+        .add_plugins(camera::CameraPlugin)
         .add_plugins(base::BasePlugin)
+        .add_plugins(chunks::ChunkPlugin)
         .add_plugins(rock::RockPlugin)
+        .add_plugins(director::DirectorPlugin)
+        .add_plugins(gravity::GravityPlugin)
+        .add_plugins(hazards::HazardPlugin)
+        .add_plugins(steering::SteeringPlugin)
+        .add_plugins(pirates::PiratePlugin)
+        .add_plugins(traders::TraderPlugin)
+        .add_plugins(drones::DronePlugin)
         .add_plugins(stats::StatsPlugin)
         .add_plugins(gold::GoldPlugin)
         .add_plugins(iron::IronPlugin)
         .add_plugins(copper::CopperPlugin)
         .add_plugins(coal::CoalPlugin)
         .add_plugins(trades::TradesPlugin)
+        .add_plugins(refinery::RefineryPlugin)
+        .add_plugins(mining::MiningPlugin)
+        .add_plugins(tractor::TractorPlugin)
+        .add_plugins(weapons::WeaponPlugin)
+        .add_plugins(shipyard::ShipyardPlugin)
         .add_systems(Startup, setup)
-        .add_systems(Update, movement)
+        .add_systems(PreUpdate, update_win_size)
+        .add_systems(FixedUpdate, (movement, wrap_world).chain())
         .run();
 }
 
@@ -169,36 +530,111 @@ fn setup(
     commands.insert_resource(game_textures);
 }
 
+// keep window size in sync after resizing or toggling fullscreen
+fn update_win_size(
+    mut win_size: ResMut<WinSize>,
+    query: Query<&Window, (With<PrimaryWindow>, Changed<Window>)>,
+) {
+    let Ok(primary) = query.single() else {
+        return;
+    };
+    let (win_w, win_h) = (primary.width(), primary.height());
+    if win_size.w != win_w || win_size.h != win_h {
+        win_size.w = win_w;
+        win_size.h = win_h;
+    }
+}
+
+// shortest offset between two points in a wrapping world
+fn wrap_offset(offset: Vec2) -> Vec2 {
+    let size = Vec2::from(WORLD_SIZE);
+    (offset + size / 2.0).rem_euclid(size) - size / 2.0
+}
+
 fn movement(
     mut commands: Commands,
-    win_size: Res<WinSize>,
-    mut query: Query<(Entity, &Velocity, &mut Transform, &Movable)>,
+    mut query: Query<(
+        Entity,
+        &mut Velocity,
+        &mut Transform,
+        &Movable,
+        Option<&Acceleration>,
+        Option<&ShipParams>,
+        Has<Player>,
+    )>,
     time: Res<Time>,
 ) {
-    for (entity, velocity, mut transform, movable) in query.iter_mut() {
-        // reduce speed when moving diagnolly
-        let mut speed_offset = 1.0;
-        if velocity.x != 0.0 && velocity.y != 0.0 {
-            speed_offset = 0.75;
+    // things far away from the player are cleaned up
+    let player_pos = query
+        .iter()
+        .find(|(.., is_player)| *is_player)
+        .map(|(_, _, transform, ..)| transform.translation.truncate());
+
+    for (entity, mut velocity, mut transform, movable, acceleration, ship, _) in query.iter_mut() {
+        let delta = time.delta_secs();
+
+        if let Some(acceleration) = acceleration {
+            velocity.x += acceleration.x * delta;
+            velocity.y += acceleration.y * delta;
+        }
+
+        // ships slow down on their own and have a top speed
+        if let Some(ship) = ship {
+            let damping = (1.0 - ship.drag * delta).max(0.0);
+            velocity.x *= damping;
+            velocity.y *= damping;
+
+            let speed = Vec2::new(velocity.x, velocity.y).length();
+            if speed > ship.max_speed {
+                velocity.x *= ship.max_speed / speed;
+                velocity.y *= ship.max_speed / speed;
+            }
         }
 
         let translation = &mut transform.translation;
-        let delta = time.delta_secs();
-        let x = translation.x + velocity.x * delta * BASE_SPEED * speed_offset;
-        let y = translation.y + velocity.y * delta * BASE_SPEED * speed_offset;
+        let x = translation.x + velocity.x * delta * BASE_SPEED;
+        let y = translation.y + velocity.y * delta * BASE_SPEED;
 
         translation.x = x;
         translation.y = y;
 
-        if movable.auto_despawn {
-            let margin = 30.0;
-            if translation.y > win_size.h / 2. + margin
-                || translation.y < -win_size.h / 2. - margin
-                || translation.x > win_size.w / 2. + margin
-                || translation.x < -win_size.w / 2. - margin
-            {
-                commands.entity(entity).despawn();
-            }
+        if movable.auto_despawn
+            && let Some(player_pos) = player_pos
+            && translation.truncate().distance(player_pos) > DESPAWN_DISTANCE
+        {
+            commands.entity(entity).despawn();
         }
     }
 }
+
+// the player re-enters from the opposite edge
+// everything else is kept at its copy closest to the player
+// so rendering and collisions just work across the seam
+fn wrap_world(
+    world_mode: Res<WorldMode>,
+    mut player_query: Query<&mut Transform, With<Player>>,
+    mut query: Query<
+        &mut Transform,
+        (
+            Or<(With<Movable>, With<Base>, With<GravityWell>)>,
+            Without<Player>,
+        ),
+    >,
+) {
+    if *world_mode != WorldMode::Wrap {
+        return;
+    }
+    let Ok(mut player_tf) = player_query.single_mut() else {
+        return;
+    };
+
+    let player_pos = wrap_offset(player_tf.translation.truncate());
+    player_tf.translation.x = player_pos.x;
+    player_tf.translation.y = player_pos.y;
+
+    for mut transform in query.iter_mut() {
+        let position = player_pos + wrap_offset(transform.translation.truncate() - player_pos);
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+    }
+}
@@ -0,0 +1,204 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use crate::{
+    GOLD_SIZE, GameTextures, MINING_HEAT_RATE, MINING_RANGE, ORE_COLLIDER, ROCK_COOL_RATE,
+    SPRITE_SCALE,
+    collision::SpatialHash,
+    components::{
+        Coal, Copper, Gold, Heat, Iron, Movable, Player, Rock, RockType, SpriteSize, Velocity,
+    },
+    input::ShipInput,
+    rock::spawn_rock,
+    trades::ResourceType,
+};
+
+pub struct MiningPlugin;
+impl Plugin for MiningPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedUpdate, mining_laser);
+    }
+}
+
+pub fn spawn_ore(
+    commands: &mut Commands,
+    game_textures: &GameTextures,
+    res_type: ResourceType,
+    position: Vec3,
+    velocity: Velocity,
+) -> Option<Entity> {
+    // only raw ores float around in space
+    let texture = match res_type {
+        ResourceType::Gold => game_textures.gold.clone(),
+        ResourceType::Iron => game_textures.iron.clone(),
+        ResourceType::Copper => game_textures.copper.clone(),
+        ResourceType::Coal => game_textures.coal.clone(),
+        ResourceType::Steel | ResourceType::Wiring => return None,
+    };
+
+    let mut ore = commands.spawn((
+        Sprite::from_image(texture),
+        Transform {
+            translation: position,
+            scale: Vec3::new(SPRITE_SCALE, SPRITE_SCALE, 1.),
+            ..Default::default()
+        },
+    ));
+    ore.insert(Movable { auto_despawn: true })
+        .insert(velocity)
+        .insert(SpriteSize::from(GOLD_SIZE))
+        .insert(ORE_COLLIDER);
+
+    match res_type {
+        ResourceType::Gold => {
+            ore.insert(Gold);
+        }
+        ResourceType::Iron => {
+            ore.insert(Iron);
+        }
+        ResourceType::Copper => {
+            ore.insert(Copper);
+        }
+        ResourceType::Coal => {
+            ore.insert(Coal);
+        }
+        ResourceType::Steel | ResourceType::Wiring => {}
+    }
+    Some(ore.id())
+}
+
+// which raw ore an entity is, used with Has<Gold>, Has<Iron> and Has<Copper>
+// anything else in an ore query is coal
+pub fn ore_type(is_gold: bool, is_iron: bool, is_copper: bool) -> ResourceType {
+    if is_gold {
+        ResourceType::Gold
+    } else if is_iron {
+        ResourceType::Iron
+    } else if is_copper {
+        ResourceType::Copper
+    } else {
+        ResourceType::Coal
+    }
+}
+
+// scatter ores around a destroyed ship
+pub fn spill_ore(
+    commands: &mut Commands,
+    game_textures: &GameTextures,
+    cargo: impl IntoIterator<Item = ResourceType>,
+    position: Vec3,
+) {
+    use rand::Rng;
+    let mut rng = rand::rng();
+    for res_type in cargo {
+        let direction = Vec2::from_angle(rng.random_range(0.0..TAU));
+        spawn_ore(
+            commands,
+            game_textures,
+            res_type,
+            position.with_z(1.0),
+            Velocity {
+                x: direction.x * 0.1,
+                y: direction.y * 0.1,
+            },
+        );
+    }
+}
+
+// hold the mine input to heat up the nearest rock in range
+// rocks cool down again when the laser moves away
+fn mining_laser(
+    mut commands: Commands,
+    game_textures: Res<GameTextures>,
+    ship_input: Res<ShipInput>,
+    spatial_hash: Res<SpatialHash>,
+    time: Res<Time>,
+    mut gizmos: Gizmos,
+    player_query: Query<&Transform, With<Player>>,
+    mut rock_query: Query<
+        (Entity, &Transform, &RockType, &mut Heat, &mut Sprite),
+        (With<Rock>, Without<Player>),
+    >,
+) {
+    let Ok(player_tf) = player_query.single() else {
+        return;
+    };
+    let player_pos = player_tf.translation.truncate();
+    let delta = time.delta_secs();
+
+    let mut target = None;
+    if ship_input.mine {
+        let mut nearest = MINING_RANGE;
+        let nearby = spatial_hash.nearby(player_pos, MINING_RANGE);
+        for (rock, rock_tf, ..) in rock_query.iter_many(&nearby) {
+            let distance = rock_tf.translation.truncate().distance(player_pos);
+            if distance < nearest {
+                nearest = distance;
+                target = Some(rock);
+            }
+        }
+    }
+
+    for (rock, rock_tf, rock_type, mut heat, mut sprite) in rock_query.iter_mut() {
+        if target == Some(rock) {
+            heat.0 += MINING_HEAT_RATE * delta;
+            gizmos.line_2d(
+                player_pos,
+                rock_tf.translation.truncate(),
+                Color::srgb(1.0, 0.3, 0.2),
+            );
+        } else if heat.0 > 0.0 {
+            heat.0 = (heat.0 - ROCK_COOL_RATE * delta).max(0.0);
+        }
+
+        // glow red while heating up
+        // only touch the sprite when the heat moved, so idle rocks stay unchanged
+        let rock_yield = rock_type.yield_table();
+        if heat.is_changed() {
+            let glow = (heat.0 / rock_yield.heat).min(1.0);
+            sprite.color = Color::srgb(1.0, 1.0 - glow, 1.0 - glow);
+        }
+
+        if heat.0 < rock_yield.heat {
+            continue;
+        }
+
+        // split into smaller rocks and drop ores
+        commands.entity(rock).despawn();
+
+        use rand::Rng;
+        let mut rng = rand::rng();
+        let position = rock_tf.translation;
+
+        for fragment in rock_yield.fragments {
+            let direction = Vec2::from_angle(rng.random_range(0.0..TAU));
+            spawn_rock(
+                &mut commands,
+                &game_textures,
+                *fragment,
+                position + (direction * 8.0).extend(0.0),
+                Velocity {
+                    x: direction.x * 0.2,
+                    y: direction.y * 0.2,
+                },
+            );
+        }
+
+        for (res_type, chance) in rock_yield.loot {
+            if rng.random_bool(*chance) {
+                let direction = Vec2::from_angle(rng.random_range(0.0..TAU));
+                spawn_ore(
+                    &mut commands,
+                    &game_textures,
+                    *res_type,
+                    position,
+                    Velocity {
+                        x: direction.x * 0.1,
+                        y: direction.y * 0.1,
+                    },
+                );
+            }
+        }
+    }
+}
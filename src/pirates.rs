@@ -0,0 +1,207 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    DESPAWN_DISTANCE, EDGE_SPAWN_MARGIN, GameOver, GameTextures, PIRATE_ESCAPE_DISTANCE,
+    PIRATE_FIRST_DIFFICULTY, PIRATE_FLEE_HEALTH, PIRATE_HULL, PIRATE_SHIP, PIRATE_SIGHT,
+    PIRATE_SPAWN_INTERVAL, PIRATES_PER_DIFFICULTY, SPRITE_SCALE, ShipStorage, WORLD_SIZE, WinSize,
+    WorldMode,
+    camera::CameraZoom,
+    collision::overlaps,
+    components::{Collider, Hull, Movable, Pirate, Player},
+    director::Director,
+    mining::spill_ore,
+    rock::screen_edge,
+    steering::{Behavior, spawn_ai_ship, steer},
+    trades::ResourceType,
+};
+
+#[derive(Resource)]
+struct PirateSpawnTimer(Timer);
+
+pub struct PiratePlugin;
+impl Plugin for PiratePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(PirateSpawnTimer(Timer::from_seconds(
+            PIRATE_SPAWN_INTERVAL,
+            TimerMode::Repeating,
+        )))
+        .add_systems(
+            FixedUpdate,
+            (
+                pirate_spawn,
+                pirate_behavior.before(steer),
+                pirate_steal,
+                pirate_escape,
+                pirate_destroyed,
+            ),
+        );
+    }
+}
+
+// more pirates show up as the director ramps up
+fn pirate_spawn(
+    mut commands: Commands,
+    game_textures: Res<GameTextures>,
+    time: Res<Time>,
+    director: Res<Director>,
+    game_over: Res<GameOver>,
+    win_size: Res<WinSize>,
+    zoom: Res<CameraZoom>,
+    mut timer: ResMut<PirateSpawnTimer>,
+    player_query: Query<&Transform, With<Player>>,
+    pirate_query: Query<(), With<Pirate>>,
+) {
+    if game_over.0 || !timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
+    if director.difficulty < PIRATE_FIRST_DIFFICULTY {
+        return;
+    }
+    let max_pirates =
+        1 + ((director.difficulty - PIRATE_FIRST_DIFFICULTY) * PIRATES_PER_DIFFICULTY) as usize;
+    if pirate_query.iter().len() >= max_pirates {
+        return;
+    }
+    let Ok(player_tf) = player_query.single() else {
+        return;
+    };
+
+    // come in from just outside the screen
+    let mut rng = rand::rng();
+    let half_view = Vec2::new(win_size.w, win_size.h) * zoom.0 / 2.0 + EDGE_SPAWN_MARGIN;
+    let offset = screen_edge(
+        rng.random_range(0..4),
+        rng.random_range(-1.0..1.0),
+        half_view,
+    )
+    .clamp_length_max(DESPAWN_DISTANCE - EDGE_SPAWN_MARGIN);
+    let position = player_tf.translation.truncate() + offset;
+
+    spawn_ai_ship(
+        &mut commands,
+        &game_textures,
+        PIRATE_SHIP,
+        PIRATE_HULL,
+        Color::srgb(1.0, 0.4, 0.4),
+        position.extend(5.0),
+        SPRITE_SCALE,
+    )
+    .insert(Pirate::default())
+    .insert(Behavior::Wander)
+    .insert(Movable { auto_despawn: true });
+}
+
+// chase a ship carrying cargo
+// run off once hurt or loaded with loot
+fn pirate_behavior(
+    ship_storage: Res<ShipStorage>,
+    player_query: Query<&Transform, With<Player>>,
+    mut pirate_query: Query<(&Transform, &Pirate, &Hull, &mut Behavior)>,
+) {
+    let Ok(player_tf) = player_query.single() else {
+        return;
+    };
+    let player_pos = player_tf.translation.truncate();
+
+    let mut ship_total = ship_storage.gold;
+    ship_total += ship_storage.iron;
+    ship_total += ship_storage.copper;
+    ship_total += ship_storage.coal;
+
+    for (pirate_tf, pirate, hull, mut behavior) in pirate_query.iter_mut() {
+        let distance = pirate_tf.translation.truncate().distance(player_pos);
+        let next = if hull.health < PIRATE_FLEE_HEALTH || !pirate.cargo.is_empty() {
+            Behavior::Flee(player_pos)
+        } else if ship_total > 0 && distance < PIRATE_SIGHT {
+            Behavior::Seek(player_pos)
+        } else {
+            Behavior::Wander
+        };
+        behavior.set_if_neq(next);
+    }
+}
+
+// touching the player empties the ship's cargo hold
+// loaded pirates stay around until they escape, so they can be chased down
+fn pirate_steal(
+    mut ship_storage: ResMut<ShipStorage>,
+    player_query: Query<(&Transform, &Collider), With<Player>>,
+    mut pirate_query: Query<(&Transform, &Collider, &mut Pirate, &mut Movable)>,
+) {
+    let Ok((player_tf, player_collider)) = player_query.single() else {
+        return;
+    };
+
+    for (pirate_tf, pirate_collider, mut pirate, mut movable) in pirate_query.iter_mut() {
+        if !pirate.cargo.is_empty()
+            || !overlaps(pirate_collider, pirate_tf, player_collider, player_tf)
+        {
+            continue;
+        }
+
+        for _ in 0..ship_storage.gold {
+            pirate.cargo.push(ResourceType::Gold);
+        }
+        for _ in 0..ship_storage.iron {
+            pirate.cargo.push(ResourceType::Iron);
+        }
+        for _ in 0..ship_storage.copper {
+            pirate.cargo.push(ResourceType::Copper);
+        }
+        for _ in 0..ship_storage.coal {
+            pirate.cargo.push(ResourceType::Coal);
+        }
+        ship_storage.gold = 0;
+        ship_storage.iron = 0;
+        ship_storage.copper = 0;
+        ship_storage.coal = 0;
+        movable.auto_despawn = pirate.cargo.is_empty();
+    }
+}
+
+// a pirate that gets far enough away is gone for good, and the loot with it
+fn pirate_escape(
+    mut commands: Commands,
+    world_mode: Res<WorldMode>,
+    player_query: Query<&Transform, With<Player>>,
+    pirate_query: Query<(Entity, &Transform, &Pirate)>,
+) {
+    let Ok(player_tf) = player_query.single() else {
+        return;
+    };
+
+    // a wrapping world keeps everything within half its size of the ship
+    let mut escape_distance = PIRATE_ESCAPE_DISTANCE;
+    if *world_mode == WorldMode::Wrap {
+        escape_distance = escape_distance.min(WORLD_SIZE.0.min(WORLD_SIZE.1) * 0.4);
+    }
+
+    for (pirate, pirate_tf, loot) in pirate_query {
+        if !loot.cargo.is_empty()
+            && pirate_tf.translation.distance(player_tf.translation) > escape_distance
+        {
+            commands.entity(pirate).despawn();
+        }
+    }
+}
+
+// destroyed pirates spill what they stole
+fn pirate_destroyed(
+    mut commands: Commands,
+    game_textures: Res<GameTextures>,
+    pirate_query: Query<(Entity, &Transform, &Pirate, &Hull)>,
+) {
+    for (pirate, pirate_tf, loot, hull) in pirate_query {
+        if hull.health > 0.0 {
+            continue;
+        }
+        commands.entity(pirate).despawn();
+        spill_ore(
+            &mut commands,
+            &game_textures,
+            loot.cargo.iter().copied(),
+            pirate_tf.translation,
+        );
+    }
+}
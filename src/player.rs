@@ -1,25 +1,36 @@
-use bevy::{
-    math::bounding::{Aabb2d, IntersectsVolume},
-    prelude::*,
-};
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+use bevy::prelude::*;
 
 use crate::{
-    BaseStorage, GameTextures, MAX_BASE_STORAGE, MAX_SHIP_STORAGE, PLAYER_SIZE, SPRITE_SCALE,
-    ShipStorage, WinSize,
-    components::{Base, Coal, Copper, Gold, Iron, Movable, Player, Rock, SpriteSize, Velocity},
+    BASE_DOCK_COLLIDER, BaseStorage, CARGO_DROP_DISTANCE, DOCK_RANGE, EMPTY_TANK_SPEED,
+    FUEL_BURN_RATE, FUEL_PER_COAL, GameOver, GameTextures, HULL_DAMAGE, MAX_BASE_STORAGE, MAX_FUEL,
+    MAX_HULL, MAX_SHIP_STORAGE, PLAYER_COLLIDER, PLAYER_SHIP, PLAYER_SIZE, PLAYER_SPAWN,
+    PlayerCash, REPAIR_COST, RESPAWN_PENALTY, SHIP_MASS, SHIP_RESTITUTION, SPRITE_SCALE,
+    ShipStorage, WEAPON_COOLDOWN, WORLD_SIZE, WorldMode,
+    collision::{SpatialHash, bounding_radius, collide, overlaps},
+    components::{
+        Acceleration, Base, Coal, Collider, Copper, Fuel, Gold, Hull, Iron, Movable, Player, Rock,
+        ShipParams, SpriteSize, TractorBeam, Velocity, Weapon,
+    },
+    input::ShipInput,
+    mining::spawn_ore,
+    trades::ResourceType,
 };
 
 pub struct PlayerPlugin;
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(PostStartup, player_spawn)
-            .add_systems(Update, (player_movement, unload_at_base));
+        app.add_systems(PostStartup, player_spawn).add_systems(
+            FixedUpdate,
+            (player_movement, unload_at_base, player_destroyed),
+        );
     }
 }
 
 fn player_spawn(mut commands: Commands, game_textures: Res<GameTextures>) {
     // spawn right under base
-    let target_position = Vec3::new(0., -40.0, 10.);
+    let target_position = PLAYER_SPAWN;
     commands
         .spawn((
             Sprite::from_image(game_textures.player.clone()),
@@ -31,29 +42,54 @@ fn player_spawn(mut commands: Commands, game_textures: Res<GameTextures>) {
         ))
         .insert(Player)
         .insert(SpriteSize::from(PLAYER_SIZE))
+        .insert(PLAYER_COLLIDER)
         .insert(Movable {
             auto_despawn: false,
         })
-        .insert(Velocity { x: 0.0, y: 0.0 });
+        .insert(Velocity { x: 0.0, y: 0.0 })
+        .insert(Acceleration { x: 0.0, y: 0.0 })
+        .insert(PLAYER_SHIP)
+        .insert(Fuel(MAX_FUEL))
+        .insert(Hull::new(MAX_HULL))
+        .insert(TractorBeam { level: 0 })
+        .insert(Weapon {
+            level: 1,
+            cooldown: Timer::from_seconds(WEAPON_COOLDOWN, TimerMode::Once),
+        });
 }
 
-fn player_movement(
+pub fn player_movement(
     mut ship_storage: ResMut<ShipStorage>,
-    input: Res<ButtonInput<KeyCode>>,
-    win_size: Res<WinSize>,
+    ship_input: Res<ShipInput>,
+    spatial_hash: Res<SpatialHash>,
+    world_mode: Res<WorldMode>,
+    time: Res<Time>,
     mut commands: Commands,
+    game_textures: Res<GameTextures>,
     mut player_query: Query<
-        (&mut Velocity, &mut Transform, &SpriteSize),
+        (
+            &mut Velocity,
+            &mut Acceleration,
+            &mut Transform,
+            &Collider,
+            &ShipParams,
+            &mut Fuel,
+            &mut Hull,
+            &TractorBeam,
+        ),
         (With<Player>, Without<Base>),
     >,
-    base_query: Query<(&Transform, &SpriteSize), With<Base>>,
-    rock_query: Query<(&Transform, &SpriteSize), (With<Rock>, Without<Base>, Without<Player>)>,
+    base_query: Query<(&Transform, &Collider), With<Base>>,
+    mut rock_query: Query<
+        (&mut Transform, &SpriteSize, &Collider, &mut Velocity),
+        (With<Rock>, Without<Base>, Without<Player>),
+    >,
     gold_query: Query<
-        (Entity, &Transform, &SpriteSize),
+        (Entity, &Transform, &Collider),
         (With<Gold>, Without<Base>, Without<Rock>, Without<Player>),
     >,
     iron_query: Query<
-        (Entity, &Transform, &SpriteSize),
+        (Entity, &Transform, &Collider),
         (
             With<Iron>,
             Without<Gold>,
@@ -63,7 +99,7 @@ fn player_movement(
         ),
     >,
     copper_query: Query<
-        (Entity, &Transform, &SpriteSize),
+        (Entity, &Transform, &Collider),
         (
             With<Copper>,
             Without<Gold>,
@@ -74,7 +110,7 @@ fn player_movement(
         ),
     >,
     coal_query: Query<
-        (Entity, &Transform, &SpriteSize),
+        (Entity, &Transform, &Collider),
         (
             With<Coal>,
             Without<Gold>,
@@ -86,144 +122,155 @@ fn player_movement(
         ),
     >,
 ) {
-    if let Ok((mut player_vel, mut player_tf, player_size)) = player_query.single_mut() {
-        let mut x = 0.0;
-        let mut y = 0.0;
-
-        let a = input.pressed(KeyCode::KeyA);
-        let left = input.pressed(KeyCode::ArrowLeft);
-
-        let d = input.pressed(KeyCode::KeyD);
-        let right = input.pressed(KeyCode::ArrowRight);
-
-        let w = input.pressed(KeyCode::KeyW);
-        let up = input.pressed(KeyCode::ArrowUp);
-
-        let s = input.pressed(KeyCode::KeyS);
-        let down = input.pressed(KeyCode::ArrowDown);
-
-        // horizontal movement
-        if a || left {
-            x = -1.0;
-            let new_rotation = Quat::from_rotation_z((90.0_f32).to_radians());
-            player_tf.rotation = new_rotation;
-        } else if d || right {
-            x = 1.0;
-            let new_rotation = Quat::from_rotation_z((270.0_f32).to_radians());
-            player_tf.rotation = new_rotation;
+    if let Ok((
+        mut player_vel,
+        mut player_accel,
+        mut player_tf,
+        player_collider,
+        ship,
+        mut fuel,
+        mut hull,
+        tractor,
+    )) = player_query.single_mut()
+    {
+        hull.cooldown.tick(time.delta());
+
+        // turn towards the aim direction or by the turn input
+        let (current, _, _) = player_tf.rotation.to_euler(EulerRot::ZYX);
+        let max_turn = ship.turn_rate * time.delta_secs();
+        if let Some(aim) = ship_input.aim {
+            // the sprite faces up when not rotated
+            let target = aim.to_angle() - FRAC_PI_2;
+            let diff = (target - current + PI).rem_euclid(TAU) - PI;
+            player_tf.rotation = Quat::from_rotation_z(current + diff.clamp(-max_turn, max_turn));
+        } else if ship_input.turn != 0.0 {
+            player_tf.rotation = Quat::from_rotation_z(current + ship_input.turn * max_turn);
         }
 
-        // vertical movement
-        if w || up {
-            y = 1.0;
-            if a || left {
-                let new_rotation = Quat::from_rotation_z((45.0_f32).to_radians());
-                player_tf.rotation = new_rotation;
-            } else if d || right {
-                let new_rotation = Quat::from_rotation_z((315.0_f32).to_radians());
-                player_tf.rotation = new_rotation;
-            } else {
-                let new_rotation = Quat::from_rotation_z((0.0_f32).to_radians());
-                player_tf.rotation = new_rotation;
+        // thrust along the ship's heading
+        player_accel.x = 0.0;
+        player_accel.y = 0.0;
+        if ship_input.thrust != 0.0 {
+            // burn fuel while thrusting
+            // an empty tank only allows slow drift
+            fuel.0 =
+                (fuel.0 - FUEL_BURN_RATE * ship_input.thrust.abs() * time.delta_secs()).max(0.0);
+            let mut thrust = ship.thrust * ship_input.thrust;
+            if fuel.0 <= 0.0 {
+                thrust *= EMPTY_TANK_SPEED;
             }
-        } else if s || down {
-            y = -1.0;
-            if a || left {
-                let new_rotation = Quat::from_rotation_z((135.0_f32).to_radians());
-                player_tf.rotation = new_rotation;
-            } else if d || right {
-                let new_rotation = Quat::from_rotation_z((225.0_f32).to_radians());
-                player_tf.rotation = new_rotation;
-            } else {
-                let new_rotation = Quat::from_rotation_z((180.0_f32).to_radians());
-                player_tf.rotation = new_rotation;
+
+            // a damaged hull slows the ship down
+            thrust *= 0.5 + 0.5 * hull.health.max(0.0) / MAX_HULL;
+
+            // when aiming, only thrust once roughly facing the aim direction
+            let forward = (player_tf.rotation * Vec3::Y).truncate();
+            if let Some(aim) = ship_input.aim {
+                thrust *= forward.dot(aim.normalize_or_zero()).max(0.0);
             }
+            player_accel.x = forward.x * thrust;
+            player_accel.y = forward.y * thrust;
         }
 
-        // player cannot move off screen
-        let tl = player_tf.translation;
-        if tl.x < -win_size.w / 2. + PLAYER_SIZE.1 / 2. && x < 0.0 {
-            player_vel.x = 0.0;
-            return;
-        }
-        if tl.x > win_size.w / 2. - PLAYER_SIZE.1 / 2. && x > 0.0 {
-            player_vel.x = 0.0;
-            return;
-        }
-        if tl.y < -win_size.h / 2. + PLAYER_SIZE.1 / 2. && y < 0.0 {
-            player_vel.y = 0.0;
-            return;
-        }
-        if tl.y > win_size.h / 2. - PLAYER_SIZE.1 / 2. && y > 0.0 {
-            player_vel.y = 0.0;
-            return;
+        // player cannot leave a bounded world
+        if *world_mode == WorldMode::Bounded {
+            let tl = player_tf.translation;
+            let max_x = WORLD_SIZE.0 / 2. - PLAYER_SIZE.1 / 2.;
+            let max_y = WORLD_SIZE.1 / 2. - PLAYER_SIZE.1 / 2.;
+            if tl.x < -max_x && player_vel.x < 0.0 {
+                player_vel.x = 0.0;
+            }
+            if tl.x > max_x && player_vel.x > 0.0 {
+                player_vel.x = 0.0;
+            }
+            if tl.y < -max_y && player_vel.y < 0.0 {
+                player_vel.y = 0.0;
+            }
+            if tl.y > max_y && player_vel.y > 0.0 {
+                player_vel.y = 0.0;
+            }
+            player_tf.translation.x = player_tf.translation.x.clamp(-max_x, max_x);
+            player_tf.translation.y = player_tf.translation.y.clamp(-max_y, max_y);
         }
 
+        // only things sharing a cell with the ship can touch it
+        let nearby = spatial_hash.nearby(
+            player_tf.translation.truncate(),
+            bounding_radius(player_collider, &player_tf),
+        );
+
         // player cannot move through base
-        // check collision with base
-        if let Ok((base_tf, base_size)) = base_query.single() {
-            let base_scale = Vec2::from(base_tf.scale.xy());
-            let player_scale = Vec2::from(player_tf.scale.xy());
-
-            let collision = Aabb2d::new(
-                base_tf.translation.truncate(),
-                (base_size.0 * base_scale) / 2.0,
-            )
-            .intersects(&Aabb2d::new(
-                player_tf.translation.truncate(),
-                (player_size.0 * player_scale) / 2.0,
-            ));
-
-            if collision {
-                player_vel.x *= -2.0;
-                player_vel.y *= -2.0;
-                return;
+        // the base doesn't move, so the ship takes all of the push
+        if let Ok((base_tf, base_collider)) = base_query.single()
+            && let Some((normal, depth)) =
+                collide(player_collider, &player_tf, base_collider, base_tf)
+        {
+            player_tf.translation -= (normal * depth).extend(0.0);
+
+            let closing = Vec2::new(player_vel.x, player_vel.y).dot(normal);
+            if closing > 0.0 {
+                player_vel.x -= (1.0 + SHIP_RESTITUTION) * closing * normal.x;
+                player_vel.y -= (1.0 + SHIP_RESTITUTION) * closing * normal.y;
             }
         }
 
         // player cannot move through rocks
-        // check collision with rocks
-        for (rock_tf, rock_size) in rock_query {
-            let rock_scale = Vec2::from(rock_tf.scale.xy());
-            let player_scale = Vec2::from(player_tf.scale.xy());
-
-            let collision = Aabb2d::new(
-                rock_tf.translation.truncate(),
-                (rock_size.0 * rock_scale) / 2.0,
-            )
-            .intersects(&Aabb2d::new(
-                player_tf.translation.truncate(),
-                (player_size.0 * player_scale) / 2.0,
-            ));
-
-            if collision {
-                if player_vel.x != 0.0 || player_vel.y != 0.0 {
-                    player_vel.x *= -8.0;
-                    player_vel.y *= -8.0;
-                } else {
-                    player_vel.x = -8.0;
-                    player_vel.y = -8.0;
+        // both get pushed apart and bounce off by their mass
+        let mut rocks = rock_query.iter_many_mut(&nearby);
+        while let Some((mut rock_tf, rock_size, rock_collider, mut rock_vel)) = rocks.fetch_next() {
+            let Some((normal, depth)) =
+                collide(player_collider, &player_tf, rock_collider, &rock_tf)
+            else {
+                continue;
+            };
+            let rock_mass = rock_size.0.x * rock_size.0.y;
+            let total_mass = SHIP_MASS + rock_mass;
+
+            player_tf.translation -= (normal * depth * rock_mass / total_mass).extend(0.0);
+            rock_tf.translation += (normal * depth * SHIP_MASS / total_mass).extend(0.0);
+
+            // only moving towards each other counts as an impact
+            let closing = (Vec2::new(player_vel.x, player_vel.y)
+                - Vec2::new(rock_vel.x, rock_vel.y))
+            .dot(normal);
+            if closing <= 0.0 {
+                continue;
+            }
+
+            // damage scales with the speed of the impact
+            if hull.cooldown.is_finished() {
+                hull.health -= closing * HULL_DAMAGE;
+                hull.cooldown.reset();
+
+                // the lost cargo floats off to the side so it can be picked up again
+                // it lands outside the tractor beam so it has to be chased down
+                if let Some(res_type) = drop_cargo(&mut ship_storage) {
+                    let side = normal.perp();
+                    let distance = tractor.radius() + CARGO_DROP_DISTANCE;
+                    spawn_ore(
+                        &mut commands,
+                        &game_textures,
+                        res_type,
+                        (player_tf.translation.truncate() + side * distance).extend(1.0),
+                        Velocity {
+                            x: side.x * 0.3,
+                            y: side.y * 0.3,
+                        },
+                    );
                 }
-                return;
             }
+
+            let impulse = (1.0 + SHIP_RESTITUTION) * closing / (1.0 / SHIP_MASS + 1.0 / rock_mass);
+            player_vel.x -= normal.x * impulse / SHIP_MASS;
+            player_vel.y -= normal.y * impulse / SHIP_MASS;
+            rock_vel.x += normal.x * impulse / rock_mass;
+            rock_vel.y += normal.y * impulse / rock_mass;
         }
 
         // player collects gold
         // check collision with gold
-        for (gold, gold_tf, gold_size) in gold_query {
-            let gold_scale = Vec2::from(gold_tf.scale.xy());
-            let player_scale = Vec2::from(player_tf.scale.xy());
-
-            let collision = Aabb2d::new(
-                gold_tf.translation.truncate(),
-                (gold_size.0 * gold_scale) / 2.0,
-            )
-            .intersects(&Aabb2d::new(
-                player_tf.translation.truncate(),
-                (player_size.0 * player_scale) / 2.0,
-            ));
-
-            if collision {
+        for (gold, gold_tf, gold_collider) in gold_query.iter_many(&nearby) {
+            if overlaps(player_collider, &player_tf, gold_collider, gold_tf) {
                 let mut ship_total = ship_storage.gold;
                 ship_total += ship_storage.iron;
                 ship_total += ship_storage.copper;
@@ -239,20 +286,8 @@ fn player_movement(
 
         // player collects iron
         // check collision with iron
-        for (iron, iron_tf, iron_size) in iron_query {
-            let iron_scale = Vec2::from(iron_tf.scale.xy());
-            let player_scale = Vec2::from(player_tf.scale.xy());
-
-            let collision = Aabb2d::new(
-                iron_tf.translation.truncate(),
-                (iron_size.0 * iron_scale) / 2.0,
-            )
-            .intersects(&Aabb2d::new(
-                player_tf.translation.truncate(),
-                (player_size.0 * player_scale) / 2.0,
-            ));
-
-            if collision {
+        for (iron, iron_tf, iron_collider) in iron_query.iter_many(&nearby) {
+            if overlaps(player_collider, &player_tf, iron_collider, iron_tf) {
                 let mut ship_total = ship_storage.gold;
                 ship_total += ship_storage.iron;
                 ship_total += ship_storage.copper;
@@ -268,20 +303,8 @@ fn player_movement(
 
         // player collects copper
         // check collision with copper
-        for (copper, copper_tf, copper_size) in copper_query {
-            let copper_scale = Vec2::from(copper_tf.scale.xy());
-            let player_scale = Vec2::from(player_tf.scale.xy());
-
-            let collision = Aabb2d::new(
-                copper_tf.translation.truncate(),
-                (copper_size.0 * copper_scale) / 2.0,
-            )
-            .intersects(&Aabb2d::new(
-                player_tf.translation.truncate(),
-                (player_size.0 * player_scale) / 2.0,
-            ));
-
-            if collision {
+        for (copper, copper_tf, copper_collider) in copper_query.iter_many(&nearby) {
+            if overlaps(player_collider, &player_tf, copper_collider, copper_tf) {
                 let mut ship_total = ship_storage.gold;
                 ship_total += ship_storage.iron;
                 ship_total += ship_storage.copper;
@@ -297,20 +320,8 @@ fn player_movement(
 
         // player collects coal
         // check collision with coal
-        for (coal, coal_tf, coal_size) in coal_query {
-            let coal_scale = Vec2::from(coal_tf.scale.xy());
-            let player_scale = Vec2::from(player_tf.scale.xy());
-
-            let collision = Aabb2d::new(
-                coal_tf.translation.truncate(),
-                (coal_size.0 * coal_scale) / 2.0,
-            )
-            .intersects(&Aabb2d::new(
-                player_tf.translation.truncate(),
-                (player_size.0 * player_scale) / 2.0,
-            ));
-
-            if collision {
+        for (coal, coal_tf, coal_collider) in coal_query.iter_many(&nearby) {
+            if overlaps(player_collider, &player_tf, coal_collider, coal_tf) {
                 let mut ship_total = ship_storage.gold;
                 ship_total += ship_storage.iron;
                 ship_total += ship_storage.copper;
@@ -323,37 +334,74 @@ fn player_movement(
                 return;
             }
         }
+    }
+}
 
-        player_vel.x = x;
-        player_vel.y = y;
+// lose one random unit of cargo, returns what was lost
+fn drop_cargo(ship_storage: &mut ShipStorage) -> Option<ResourceType> {
+    let mut cargo = vec![];
+    if ship_storage.gold > 0 {
+        cargo.push((ResourceType::Gold, &mut ship_storage.gold));
+    }
+    if ship_storage.iron > 0 {
+        cargo.push((ResourceType::Iron, &mut ship_storage.iron));
+    }
+    if ship_storage.copper > 0 {
+        cargo.push((ResourceType::Copper, &mut ship_storage.copper));
+    }
+    if ship_storage.coal > 0 {
+        cargo.push((ResourceType::Coal, &mut ship_storage.coal));
     }
+    if cargo.is_empty() {
+        return None;
+    }
+
+    use rand::Rng;
+    let mut rng = rand::rng();
+    let index = rng.random_range(0..cargo.len());
+    let (res_type, amount) = &mut cargo[index];
+    **amount -= 1;
+    Some(*res_type)
 }
 
 fn unload_at_base(
     mut ship_storage: ResMut<ShipStorage>,
     mut base_storage: ResMut<BaseStorage>,
-    mut player_query: Query<(&Transform, &SpriteSize), With<Player>>,
-    base_query: Query<(&Transform, &SpriteSize), With<Base>>,
+    mut player_cash: ResMut<PlayerCash>,
+    ship_input: Res<ShipInput>,
+    mut player_query: Query<(&Transform, &Collider, &mut Fuel, &mut Hull), With<Player>>,
+    base_query: Query<&Transform, With<Base>>,
 ) {
-    if let Ok((player_tf, player_size)) = player_query.single_mut() {
-        if let Ok((base_tf, base_size)) = base_query.single() {
-            let base_scale = Vec2::from(base_tf.scale.xy());
-            let player_scale = Vec2::from(player_tf.scale.xy());
-
-            let collision = Aabb2d::new(
-                base_tf.translation.truncate(),
-                (base_size.0 * base_scale) / 2.0,
-            )
-            .intersects(&Aabb2d::new(
-                player_tf.translation.truncate(),
-                (player_size.0 * player_scale) / 2.0,
-            ));
-
-            if collision {
+    if let Ok((player_tf, player_collider, mut fuel, mut hull)) = player_query.single_mut() {
+        if let Ok(base_tf) = base_query.single() {
+            // the ship gets pushed out of the base itself
+            // so being right next to it counts as touching
+            let collision = overlaps(player_collider, player_tf, &BASE_DOCK_COLLIDER, base_tf);
+
+            // holding dock near the base also counts as docked
+            let docked =
+                ship_input.dock && player_tf.translation.distance(base_tf.translation) < DOCK_RANGE;
+
+            if collision || docked {
+                // refuel from base coal
+                // only burn a coal when all of it fits in the tank
+                while base_storage.coal > 0 && fuel.0 <= MAX_FUEL - FUEL_PER_COAL {
+                    base_storage.coal -= 1;
+                    fuel.0 += FUEL_PER_COAL;
+                }
+
+                // repair hull for credits
+                while hull.health < MAX_HULL && player_cash.0 >= REPAIR_COST {
+                    player_cash.0 -= REPAIR_COST;
+                    hull.health = (hull.health + 1.0).min(MAX_HULL);
+                }
+
                 let mut base_total = base_storage.gold;
                 base_total += base_storage.iron;
                 base_total += base_storage.copper;
                 base_total += base_storage.coal;
+                base_total += base_storage.steel;
+                base_total += base_storage.wiring;
 
                 let mut ship_total = ship_storage.gold;
                 ship_total += ship_storage.iron;
@@ -377,3 +425,36 @@ fn unload_at_base(
         }
     }
 }
+
+// respawn under the base when the hull is destroyed
+// pay a penalty or end the run
+fn player_destroyed(
+    mut game_over: ResMut<GameOver>,
+    mut player_cash: ResMut<PlayerCash>,
+    mut ship_storage: ResMut<ShipStorage>,
+    mut player_query: Query<(&mut Transform, &mut Velocity, &mut Fuel, &mut Hull), With<Player>>,
+) {
+    if let Ok((mut player_tf, mut player_vel, mut fuel, mut hull)) = player_query.single_mut() {
+        if hull.health > 0.0 {
+            return;
+        }
+
+        if player_cash.0 < RESPAWN_PENALTY {
+            game_over.0 = true;
+            return;
+        }
+        player_cash.0 -= RESPAWN_PENALTY;
+
+        ship_storage.gold = 0;
+        ship_storage.iron = 0;
+        ship_storage.copper = 0;
+        ship_storage.coal = 0;
+
+        player_tf.translation = PLAYER_SPAWN;
+        player_tf.rotation = Quat::IDENTITY;
+        player_vel.x = 0.0;
+        player_vel.y = 0.0;
+        fuel.0 = MAX_FUEL;
+        hull.health = MAX_HULL;
+    }
+}
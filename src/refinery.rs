@@ -0,0 +1,94 @@
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+
+use crate::{BaseStorage, GameOver, MAX_BASE_STORAGE, RECIPES, components::Refinery};
+
+pub struct RefineryPlugin;
+impl Plugin for RefineryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PostStartup, refinery_spawn)
+            .add_systems(Update, refinery_update)
+            .add_systems(EguiPrimaryContextPass, generate_refinery);
+    }
+}
+
+fn refinery_spawn(mut commands: Commands) {
+    commands.spawn(Refinery::default());
+}
+
+// finish the running job and move its output into base storage
+// a finished job waits until the base has room for it
+fn refinery_update(
+    time: Res<Time>,
+    mut refinery: Single<&mut Refinery>,
+    mut base: ResMut<BaseStorage>,
+    game_over: Res<GameOver>,
+) {
+    if game_over.0 {
+        return;
+    }
+    let Some(index) = refinery.recipe else {
+        return;
+    };
+
+    refinery.timer.tick(time.delta());
+    let recipe = &RECIPES[index];
+    if refinery.timer.is_finished() && base.total() + recipe.output.1 < MAX_BASE_STORAGE {
+        *base.amount_mut(recipe.output.0) += recipe.output.1;
+        refinery.recipe = None;
+    }
+}
+
+fn generate_refinery(
+    mut contexts: EguiContexts,
+    mut refinery: Single<&mut Refinery>,
+    mut base: ResMut<BaseStorage>,
+    game_over: Res<GameOver>,
+) -> Result {
+    if game_over.0 {
+        return Ok(());
+    }
+    egui::Window::new("Refinery").show(contexts.ctx_mut()?, |ui| {
+        for (index, recipe) in RECIPES.iter().enumerate() {
+            // inputs are consumed when the job starts
+            let available = recipe
+                .inputs
+                .iter()
+                .all(|(res_type, amount)| base.amount(*res_type) >= *amount);
+            let idle = refinery.recipe.is_none();
+
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(idle && available, egui::Button::new("Craft"))
+                    .clicked()
+                {
+                    for (res_type, amount) in recipe.inputs {
+                        *base.amount_mut(*res_type) -= amount;
+                    }
+                    refinery.recipe = Some(index);
+                    refinery.timer = Timer::from_seconds(recipe.seconds, TimerMode::Once);
+                }
+                ui.label(recipe.name);
+
+                let inputs: Vec<String> = recipe
+                    .inputs
+                    .iter()
+                    .map(|(res_type, amount)| format!("{} {}", amount, res_type.name()))
+                    .collect();
+                ui.label(format!(
+                    "{} -> {} {}",
+                    inputs.join(" + "),
+                    recipe.output.1,
+                    recipe.output.0.name()
+                ));
+                ui.label(format!("{}s", recipe.seconds));
+            });
+
+            if refinery.recipe == Some(index) {
+                ui.add(egui::ProgressBar::new(refinery.timer.fraction()).show_percentage());
+            }
+            ui.separator();
+        }
+    });
+    Ok(())
+}
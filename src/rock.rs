@@ -1,91 +1,297 @@
-use bevy::{
-    math::bounding::{Aabb2d, IntersectsVolume},
-    prelude::*,
-};
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
 
 use crate::{
-    GameTextures, MAX_ROCKS, ROCK1_SIZE, ROCK2_SIZE, ROCK3_SIZE, ROCK4_SIZE, SPRITE_SCALE, WinSize,
-    components::{Base, Movable, Rock, SpriteSize, Velocity},
+    BASE_CLEAR_RADIUS, BASE_DESPAWN_COLLIDER, DESPAWN_DISTANCE, EDGE_SPAWN_CHANCE,
+    EDGE_SPAWN_MARGIN, GameTextures, ROCK_RESTITUTION, ROCK_SPLIT_SPEED, ROCK1_COLLIDER,
+    ROCK1_SIZE, ROCK1_YIELD, ROCK2_COLLIDER, ROCK2_SIZE, ROCK2_YIELD, ROCK3_COLLIDER, ROCK3_SIZE,
+    ROCK3_YIELD, ROCK4_COLLIDER, ROCK4_SIZE, ROCK4_YIELD, RockYield, SPRITE_SCALE, WinSize,
+    camera::CameraZoom,
+    collision::{SpatialHash, bounding_radius, collide, overlaps},
+    components::{Base, Collider, Heat, Movable, Player, Rock, RockType, SpriteSize, Velocity},
 };
 
+// rocks also drift in from just outside the screen
+#[derive(Resource, Default)]
+pub struct EdgeEntry(pub bool);
+
 pub struct RockPlugin;
 impl Plugin for RockPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, (rock_movement, rock_spawn));
+        app.init_resource::<EdgeEntry>()
+            .add_systems(FixedUpdate, (rock_movement, rock_collisions, edge_spawn));
+    }
+}
+
+impl RockType {
+    fn texture(&self, game_textures: &GameTextures) -> Handle<Image> {
+        match self {
+            RockType::Rock1 => game_textures.rock1.clone(),
+            RockType::Rock2 => game_textures.rock2.clone(),
+            RockType::Rock3 => game_textures.rock3.clone(),
+            RockType::Rock4 => game_textures.rock4.clone(),
+        }
+    }
+
+    fn size(&self) -> (f32, f32) {
+        match self {
+            RockType::Rock1 => ROCK1_SIZE,
+            RockType::Rock2 => ROCK2_SIZE,
+            RockType::Rock3 => ROCK3_SIZE,
+            RockType::Rock4 => ROCK4_SIZE,
+        }
+    }
+
+    fn collider(&self) -> Collider {
+        match self {
+            RockType::Rock1 => ROCK1_COLLIDER,
+            RockType::Rock2 => ROCK2_COLLIDER,
+            RockType::Rock3 => ROCK3_COLLIDER,
+            RockType::Rock4 => ROCK4_COLLIDER,
+        }
+    }
+
+    pub fn yield_table(&self) -> &'static RockYield {
+        match self {
+            RockType::Rock1 => &ROCK1_YIELD,
+            RockType::Rock2 => &ROCK2_YIELD,
+            RockType::Rock3 => &ROCK3_YIELD,
+            RockType::Rock4 => &ROCK4_YIELD,
+        }
+    }
+}
+
+pub fn spawn_rock(
+    commands: &mut Commands,
+    game_textures: &GameTextures,
+    rock_type: RockType,
+    position: Vec3,
+    velocity: Velocity,
+) -> Entity {
+    commands
+        .spawn((
+            Sprite::from_image(rock_type.texture(game_textures)),
+            Transform {
+                translation: position,
+                scale: Vec3::new(SPRITE_SCALE, SPRITE_SCALE, 1.),
+                ..Default::default()
+            },
+        ))
+        .insert(Rock)
+        .insert(rock_type)
+        .insert(Heat(0.0))
+        .insert(Movable { auto_despawn: true })
+        .insert(velocity)
+        .insert(SpriteSize::from(rock_type.size()))
+        .insert(rock_type.collider())
+        .id()
+}
+
+// rocks never show up on top of the base
+pub fn clear_of_base(base_query: &Query<&Transform, With<Base>>, position: Vec2) -> bool {
+    base_query
+        .iter()
+        .all(|base_tf| base_tf.translation.truncate().distance(position) >= BASE_CLEAR_RADIUS)
+}
+
+// spot on one of the screen edges, relative to the player
+// sides are left, right, bottom, top and along goes from -1 to 1
+pub fn screen_edge(side: u32, along: f32, half_view: Vec2) -> Vec2 {
+    match side {
+        0 => Vec2::new(-half_view.x, along * half_view.y),
+        1 => Vec2::new(half_view.x, along * half_view.y),
+        2 => Vec2::new(along * half_view.x, -half_view.y),
+        _ => Vec2::new(along * half_view.x, half_view.y),
     }
 }
 
-fn rock_spawn(
+fn edge_spawn(
     mut commands: Commands,
     game_textures: Res<GameTextures>,
+    edge_entry: Res<EdgeEntry>,
     win_size: Res<WinSize>,
-    rock_query: Query<&Rock>,
+    zoom: Res<CameraZoom>,
+    player_query: Query<&Transform, With<Player>>,
+    base_query: Query<&Transform, With<Base>>,
 ) {
-    if rock_query.iter().len() > MAX_ROCKS {
+    if !edge_entry.0 {
         return;
     }
 
-    let mut rocks = vec![];
-    rocks.push((game_textures.rock1.clone(), ROCK1_SIZE));
-    rocks.push((game_textures.rock2.clone(), ROCK2_SIZE));
-    rocks.push((game_textures.rock3.clone(), ROCK3_SIZE));
-    rocks.push((game_textures.rock4.clone(), ROCK4_SIZE));
-
-    for rock in &rocks {
-        use rand::Rng;
-        let mut rng = rand::rng();
-        let offset = 0.0;
-        let w_span = win_size.w / 2.0 + offset;
-        let h_span = win_size.h / 2.0 + offset;
-        let x = rng.random_range(-w_span..w_span);
-        let y = rng.random_range(-h_span..h_span);
-        let z = 1.0;
-        let target_position = Vec3::new(x, y, z);
-
-        commands
-            .spawn((
-                Sprite::from_image(rock.0.clone()),
-                Transform {
-                    translation: target_position,
-                    scale: Vec3::new(SPRITE_SCALE, SPRITE_SCALE, 1.),
-                    ..Default::default()
-                },
-            ))
-            .insert(Rock)
-            .insert(Movable { auto_despawn: true })
-            .insert(Velocity {
-                x: x / 5000.0,
-                y: y / 5000.0,
-            })
-            .insert(SpriteSize::from(rock.1));
+    use rand::Rng;
+    let mut rng = rand::rng();
+    if !rng.random_bool(EDGE_SPAWN_CHANCE) {
+        return;
+    }
+
+    let Ok(player_tf) = player_query.single() else {
+        return;
+    };
+    let player_pos = player_tf.translation.truncate();
+    let half_view = Vec2::new(win_size.w, win_size.h) * zoom.0 / 2.0 + EDGE_SPAWN_MARGIN;
+
+    let offset = screen_edge(
+        rng.random_range(0..4),
+        rng.random_range(-1.0..1.0),
+        half_view,
+    );
+    // too far out when zoomed out, it would be cleaned up right away
+    if offset.length() > DESPAWN_DISTANCE {
+        return;
     }
+    let position = player_pos + offset;
+    if !clear_of_base(&base_query, position) {
+        return;
+    }
+
+    // drift across the screen
+    let target = player_pos
+        + Vec2::new(
+            rng.random_range(-0.5..0.5) * half_view.x,
+            rng.random_range(-0.5..0.5) * half_view.y,
+        );
+    let drift = (target - position).normalize_or_zero() * rng.random_range(0.2..0.5);
+
+    let rocks = [
+        RockType::Rock1,
+        RockType::Rock2,
+        RockType::Rock3,
+        RockType::Rock4,
+    ];
+
+    spawn_rock(
+        &mut commands,
+        &game_textures,
+        rocks[rng.random_range(0..rocks.len())],
+        position.extend(1.0),
+        Velocity {
+            x: drift.x,
+            y: drift.y,
+        },
+    );
 }
 
 // move rocks slowly
 // despawn when colliding with base
 fn rock_movement(
     mut commands: Commands,
-    base_query: Query<(&Transform, &SpriteSize), With<Base>>,
-    rock_query: Query<(Entity, &Transform, &SpriteSize), With<Rock>>,
+    spatial_hash: Res<SpatialHash>,
+    base_query: Query<&Transform, With<Base>>,
+    rock_query: Query<(Entity, &Transform, &Collider), With<Rock>>,
 ) {
-    if let Ok((base_tf, base_size)) = base_query.single() {
-        let base_scale = Vec2::from(base_tf.scale.xy());
-
-        for (rock, rock_tf, rock_size) in rock_query {
-            let rock_scale = Vec2::from(rock_tf.scale.xy());
-
-            let collision = Aabb2d::new(
-                base_tf.translation.truncate(),
-                base_size.0 * 2.0 * base_scale,
-            )
-            .intersects(&Aabb2d::new(
-                rock_tf.translation.truncate(),
-                rock_size.0 * rock_scale,
-            ));
-
-            if collision {
+    if let Ok(base_tf) = base_query.single() {
+        let nearby = spatial_hash.nearby(
+            base_tf.translation.truncate(),
+            bounding_radius(&BASE_DESPAWN_COLLIDER, base_tf),
+        );
+        for (rock, rock_tf, rock_collider) in rock_query.iter_many(nearby) {
+            if overlaps(&BASE_DESPAWN_COLLIDER, base_tf, rock_collider, rock_tf) {
                 commands.entity(rock).despawn();
             }
         }
     }
 }
+
+// rocks bounce off each other, heavier rocks push lighter ones around
+// big rocks break apart when hit hard enough
+fn rock_collisions(
+    mut commands: Commands,
+    game_textures: Res<GameTextures>,
+    spatial_hash: Res<SpatialHash>,
+    mut rock_query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut Velocity,
+            &SpriteSize,
+            &Collider,
+            &RockType,
+        ),
+        With<Rock>,
+    >,
+) {
+    let mut splits = vec![];
+
+    // only rocks sharing a cell can touch, each pair is checked once
+    let mut pairs = vec![];
+    for (a, a_tf, _, _, a_collider, _) in rock_query.iter() {
+        let nearby = spatial_hash.nearby(
+            a_tf.translation.truncate(),
+            bounding_radius(a_collider, a_tf),
+        );
+        for b in nearby {
+            if a < b {
+                pairs.push([a, b]);
+            }
+        }
+    }
+
+    for pair in pairs {
+        let Ok(
+            [
+                (a, mut a_tf, mut a_vel, a_size, a_collider, a_type),
+                (b, mut b_tf, mut b_vel, b_size, b_collider, b_type),
+            ],
+        ) = rock_query.get_many_mut(pair)
+        else {
+            continue;
+        };
+        let Some((normal, overlap)) = collide(a_collider, &a_tf, b_collider, &b_tf) else {
+            continue;
+        };
+        let a_mass = a_size.0.x * a_size.0.y;
+        let b_mass = b_size.0.x * b_size.0.y;
+        let total_mass = a_mass + b_mass;
+
+        // push them apart so they don't stick together
+        a_tf.translation -= (normal * overlap * b_mass / total_mass).extend(0.0);
+        b_tf.translation += (normal * overlap * a_mass / total_mass).extend(0.0);
+
+        // only bounce when moving towards each other
+        let closing = Vec2::new(a_vel.x - b_vel.x, a_vel.y - b_vel.y).dot(normal);
+        if closing <= 0.0 {
+            continue;
+        }
+        let impulse = (1.0 + ROCK_RESTITUTION) * closing / (1.0 / a_mass + 1.0 / b_mass);
+        a_vel.x -= normal.x * impulse / a_mass;
+        a_vel.y -= normal.y * impulse / a_mass;
+        b_vel.x += normal.x * impulse / b_mass;
+        b_vel.y += normal.y * impulse / b_mass;
+
+        if closing > ROCK_SPLIT_SPEED {
+            if *a_type == RockType::Rock2 {
+                splits.push((a, a_tf.translation, Vec2::new(a_vel.x, a_vel.y)));
+            }
+            if *b_type == RockType::Rock2 {
+                splits.push((b, b_tf.translation, Vec2::new(b_vel.x, b_vel.y)));
+            }
+        }
+    }
+
+    use rand::Rng;
+    let mut rng = rand::rng();
+    let mut split = vec![];
+    for (rock, position, velocity) in splits {
+        // a rock hit twice in one step only breaks once
+        if split.contains(&rock) {
+            continue;
+        }
+        split.push(rock);
+        commands.entity(rock).despawn();
+
+        for fragment in RockType::Rock2.yield_table().fragments {
+            let direction = Vec2::from_angle(rng.random_range(0.0..TAU));
+            spawn_rock(
+                &mut commands,
+                &game_textures,
+                *fragment,
+                position + (direction * 8.0).extend(0.0),
+                Velocity {
+                    x: velocity.x + direction.x * 0.2,
+                    y: velocity.y + direction.y * 0.2,
+                },
+            );
+        }
+    }
+}
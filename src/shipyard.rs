@@ -0,0 +1,70 @@
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, EguiPrimaryContextPass, egui};
+
+use crate::{
+    GameOver, MAX_TRACTOR_LEVEL, MAX_WEAPON_LEVEL, PlayerCash, TRACTOR_UPGRADE_COST,
+    WEAPON_UPGRADE_COST,
+    components::{Player, TractorBeam, Weapon},
+};
+
+use thousands::Separable;
+
+pub struct ShipyardPlugin;
+impl Plugin for ShipyardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(EguiPrimaryContextPass, generate_shipyard);
+    }
+}
+
+fn generate_shipyard(
+    mut contexts: EguiContexts,
+    mut cash: ResMut<PlayerCash>,
+    mut tractor: Single<&mut TractorBeam, With<Player>>,
+    mut weapon: Single<&mut Weapon, With<Player>>,
+    game_over: Res<GameOver>,
+) -> Result {
+    if game_over.0 {
+        return Ok(());
+    }
+    egui::Window::new("Shipyard").show(contexts.ctx_mut()?, |ui| {
+        // tractor beam upgrades
+        ui.horizontal(|ui| {
+            let upgradable = tractor.level < MAX_TRACTOR_LEVEL;
+            if ui
+                .add_enabled(upgradable, egui::Button::new("Upgrade"))
+                .clicked()
+                && cash.0 >= TRACTOR_UPGRADE_COST
+            {
+                cash.0 -= TRACTOR_UPGRADE_COST;
+                tractor.level += 1;
+            }
+            ui.label(format!(
+                "Tractor Beam {}/{}",
+                tractor.level, MAX_TRACTOR_LEVEL
+            ));
+            ui.label(format!("{}", TRACTOR_UPGRADE_COST.separate_with_commas()));
+            ui.label("Galactic Credits");
+        });
+        ui.separator();
+        // more hardpoints fire more shots at once
+        ui.horizontal(|ui| {
+            let upgradable = weapon.level < MAX_WEAPON_LEVEL;
+            if ui
+                .add_enabled(upgradable, egui::Button::new("Upgrade"))
+                .clicked()
+                && cash.0 >= WEAPON_UPGRADE_COST
+            {
+                cash.0 -= WEAPON_UPGRADE_COST;
+                weapon.level += 1;
+            }
+            ui.label(format!(
+                "Weapon Hardpoints {}/{}",
+                weapon.level, MAX_WEAPON_LEVEL
+            ));
+            ui.label(format!("{}", WEAPON_UPGRADE_COST.separate_with_commas()));
+            ui.label("Galactic Credits");
+        });
+        ui.separator();
+    });
+    Ok(())
+}
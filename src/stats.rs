@@ -1,11 +1,13 @@
 use bevy::prelude::*;
 
 use crate::{
-    BaseStorage, GameOver, GameTextures, MAX_BASE_STORAGE, MAX_SHIP_STORAGE, PlayerCash,
-    SPRITE_SCALE, ShipStorage, WinSize,
+    BaseStorage, GameOver, GameTextures, MAX_BASE_STORAGE, MAX_FUEL, MAX_HULL, MAX_SHIP_STORAGE,
+    PlayerCash, SPRITE_SCALE, ShipStorage, WinSize,
+    camera::CameraZoom,
     components::{
-        BaseStorageUi, CoalCount, CopperCount, GoldCount, IronCount, MaintenanceTimer,
-        PlayerCashUi, ShipStorageUi, Stats,
+        BaseStorageUi, CoalCount, CopperCount, Fuel, FuelUi, GoldCount, Hud, Hull, HullUi,
+        IronCount, MaintenanceTimer, Player, PlayerCashUi, ShipStorageUi, Stats, SteelCount,
+        WiringCount,
     },
 };
 
@@ -14,16 +16,29 @@ use thousands::Separable;
 pub struct StatsPlugin;
 impl Plugin for StatsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(PostStartup, stats_spawn)
-            .add_systems(Update, update_stats);
+        app.add_systems(PostStartup, stats_spawn).add_systems(
+            Update,
+            (update_stats, update_fuel, update_hull, stats_resize),
+        );
     }
 }
 
-fn stats_spawn(mut commands: Commands, game_textures: Res<GameTextures>, win_size: Res<WinSize>) {
+fn stats_position(win_size: &WinSize) -> Vec3 {
     let bottom = -win_size.h / 2.0;
-    let target_position = Vec3::new(0., bottom / 2. * SPRITE_SCALE + 20., 2.0);
+    Vec3::new(0., bottom / 2. * SPRITE_SCALE + 20., 2.0)
+}
+
+fn stats_spawn(
+    mut commands: Commands,
+    game_textures: Res<GameTextures>,
+    win_size: Res<WinSize>,
+    camera: Single<Entity, With<Camera2d>>,
+) {
+    // the stats panel moves with the camera
+    let target_position = stats_position(&win_size);
     commands
         .spawn((
+            ChildOf(*camera),
             Sprite::from_image(game_textures.stats.clone()),
             Transform {
                 translation: target_position,
@@ -31,9 +46,11 @@ fn stats_spawn(mut commands: Commands, game_textures: Res<GameTextures>, win_siz
                 ..Default::default()
             },
         ))
-        .insert(Stats);
+        .insert(Stats)
+        .insert(Hud);
 
     commands.spawn((
+        Hud,
         Node {
             width: Val::Percent(100.0),
             height: Val::Percent(100.0),
@@ -93,11 +110,38 @@ fn stats_spawn(mut commands: Commands, game_textures: Res<GameTextures>, win_siz
                     margin: UiRect::all(Val::Px(13.0)),
                     ..default()
                 },
+            ),
+            (
+                SteelCount,
+                Text::default(),
+                BackgroundColor(Color::srgba(0.04, 0.04, 0.04, 0.8)),
+                TextFont {
+                    font_size: 12.0,
+                    ..Default::default()
+                },
+                Node {
+                    margin: UiRect::all(Val::Px(13.0)),
+                    ..default()
+                },
+            ),
+            (
+                WiringCount,
+                Text::default(),
+                BackgroundColor(Color::srgba(0.04, 0.04, 0.04, 0.8)),
+                TextFont {
+                    font_size: 12.0,
+                    ..Default::default()
+                },
+                Node {
+                    margin: UiRect::all(Val::Px(13.0)),
+                    ..default()
+                },
             )
         ],
     ));
 
     commands.spawn((
+        Hud,
         Node {
             width: Val::Percent(100.0),
             height: Val::Percent(100.0),
@@ -110,10 +154,26 @@ fn stats_spawn(mut commands: Commands, game_textures: Res<GameTextures>, win_siz
                 flex_direction: FlexDirection::Row,
                 align_items: AlignItems::Center,
                 justify_content: JustifyContent::SpaceAround,
-                width: Val::Px(250.0),
+                width: Val::Px(410.0),
                 ..default()
             },
             children![
+                (
+                    Text::default(),
+                    TextFont {
+                        font_size: 12.0,
+                        ..Default::default()
+                    },
+                    FuelUi,
+                ),
+                (
+                    Text::default(),
+                    TextFont {
+                        font_size: 12.0,
+                        ..Default::default()
+                    },
+                    HullUi,
+                ),
                 (
                     Text::default(),
                     TextFont {
@@ -149,6 +209,22 @@ fn stats_spawn(mut commands: Commands, game_textures: Res<GameTextures>, win_siz
     commands.spawn(MaintenanceTimer::default());
 }
 
+// keep the stats panel at the bottom of the window at any zoom
+fn stats_resize(
+    win_size: Res<WinSize>,
+    zoom: Res<CameraZoom>,
+    mut stats_query: Query<&mut Transform, With<Stats>>,
+) {
+    if !win_size.is_changed() && !zoom.is_changed() {
+        return;
+    }
+    for mut stats_tf in stats_query.iter_mut() {
+        let position = stats_position(&win_size);
+        stats_tf.translation = (position.truncate() * zoom.0).extend(position.z);
+        stats_tf.scale = Vec3::new(SPRITE_SCALE * zoom.0, SPRITE_SCALE * zoom.0, 1.);
+    }
+}
+
 fn update_stats(
     time: Res<Time>,
     mut game_over: ResMut<GameOver>,
@@ -208,6 +284,33 @@ fn update_stats(
             Without<BaseStorageUi>,
         ),
     >,
+    steel_count: Single<
+        &mut Text,
+        (
+            With<SteelCount>,
+            Without<CoalCount>,
+            Without<CopperCount>,
+            Without<IronCount>,
+            Without<GoldCount>,
+            Without<PlayerCashUi>,
+            Without<ShipStorageUi>,
+            Without<BaseStorageUi>,
+        ),
+    >,
+    wiring_count: Single<
+        &mut Text,
+        (
+            With<WiringCount>,
+            Without<SteelCount>,
+            Without<CoalCount>,
+            Without<CopperCount>,
+            Without<IronCount>,
+            Without<GoldCount>,
+            Without<PlayerCashUi>,
+            Without<ShipStorageUi>,
+            Without<BaseStorageUi>,
+        ),
+    >,
 ) {
     // add ship maintenance costs: $100/30sec
     maintenance_timer.0.tick(time.delta());
@@ -228,6 +331,8 @@ fn update_stats(
     base_total += base_storage.iron;
     base_total += base_storage.copper;
     base_total += base_storage.coal;
+    base_total += base_storage.steel;
+    base_total += base_storage.wiring;
 
     let mut gold = gold_count.into_inner();
     gold.0 = format!("Gold: {}", base_storage.gold);
@@ -241,6 +346,12 @@ fn update_stats(
     let mut coal = coal_count.into_inner();
     coal.0 = format!("Coal: {}", base_storage.coal);
 
+    let mut steel = steel_count.into_inner();
+    steel.0 = format!("Steel: {}", base_storage.steel);
+
+    let mut wiring = wiring_count.into_inner();
+    wiring.0 = format!("Wiring: {}", base_storage.wiring);
+
     let mut ship_storage_text = ship_storage_ui.into_inner();
     ship_storage_text.0 = format!("{}/{}", ship_total, MAX_SHIP_STORAGE);
 
@@ -250,3 +361,13 @@ fn update_stats(
     let mut player_cash_text = player_cash_ui.into_inner();
     player_cash_text.0 = format!("{}", player_cash.0.separate_with_commas());
 }
+
+fn update_fuel(fuel: Single<&Fuel, With<Player>>, fuel_ui: Single<&mut Text, With<FuelUi>>) {
+    let mut fuel_text = fuel_ui.into_inner();
+    fuel_text.0 = format!("Fuel: {:.0}%", fuel.0 / MAX_FUEL * 100.0);
+}
+
+fn update_hull(hull: Single<&Hull, With<Player>>, hull_ui: Single<&mut Text, With<HullUi>>) {
+    let mut hull_text = hull_ui.into_inner();
+    hull_text.0 = format!("Hull: {:.0}%", hull.health.max(0.0) / MAX_HULL * 100.0);
+}
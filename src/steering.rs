@@ -0,0 +1,189 @@
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    ARRIVE_DISTANCE, AVOID_DISTANCE, AVOID_WEIGHT, GameTextures, HULL_DAMAGE, PLAYER_COLLIDER,
+    PLAYER_SIZE, SHIP_RESTITUTION,
+    collision::{SpatialHash, bounding_radius, collide},
+    components::{Acceleration, Collider, Hull, Rock, ShipParams, SpriteSize, Velocity},
+};
+
+// what an ai ship is trying to do, targets are updated by whoever owns the ship
+#[derive(Component, Clone, Copy, PartialEq)]
+pub enum Behavior {
+    Idle,
+    Wander,
+    Seek(Vec2),
+    Flee(Vec2),
+    // like seek but slows down when getting close
+    Arrive(Vec2),
+}
+
+pub struct SteeringPlugin;
+impl Plugin for SteeringPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedUpdate, (steer, ship_collisions));
+    }
+}
+
+// the parts every ai ship shares, the owner adds its own marker, behavior and movable
+pub fn spawn_ai_ship<'a>(
+    commands: &'a mut Commands,
+    game_textures: &GameTextures,
+    ship: ShipParams,
+    hull: f32,
+    color: Color,
+    position: Vec3,
+    scale: f32,
+) -> EntityCommands<'a> {
+    let mut entity = commands.spawn((
+        Sprite {
+            image: game_textures.player.clone(),
+            color,
+            ..Default::default()
+        },
+        Transform {
+            translation: position,
+            scale: Vec3::new(scale, scale, 1.),
+            ..Default::default()
+        },
+    ));
+    entity
+        .insert(ship)
+        .insert(Hull::new(hull))
+        .insert(Velocity { x: 0.0, y: 0.0 })
+        .insert(Acceleration { x: 0.0, y: 0.0 })
+        .insert(SpriteSize::from(PLAYER_SIZE))
+        .insert(PLAYER_COLLIDER);
+    entity
+}
+
+pub fn seek(position: Vec2, target: Vec2) -> Vec2 {
+    (target - position).normalize_or_zero()
+}
+
+pub fn flee(position: Vec2, threat: Vec2) -> Vec2 {
+    (position - threat).normalize_or_zero()
+}
+
+pub fn arrive(position: Vec2, target: Vec2) -> Vec2 {
+    let offset = target - position;
+    offset.normalize_or_zero() * (offset.length() / ARRIVE_DISTANCE).min(1.0)
+}
+
+// wobble around the current heading
+pub fn wander(rng: &mut impl Rng, forward: Vec2) -> Vec2 {
+    Vec2::from_angle(rng.random_range(-0.3..0.3)).rotate(forward) * 0.5
+}
+
+// push away from obstacles, stronger the closer they are
+pub fn avoid(position: Vec2, obstacles: impl IntoIterator<Item = (Vec2, f32)>) -> Vec2 {
+    let mut push = Vec2::ZERO;
+    for (center, radius) in obstacles {
+        let offset = position - center;
+        let gap = offset.length() - radius;
+        if gap < AVOID_DISTANCE {
+            push += offset.normalize_or_zero() * (1.0 - gap.max(0.0) / AVOID_DISTANCE);
+        }
+    }
+    push
+}
+
+// turn the behavior into a direction, steer around rocks
+// then turn and thrust the same way the player's ship does
+pub fn steer(
+    time: Res<Time>,
+    spatial_hash: Res<SpatialHash>,
+    mut ship_query: Query<
+        (&Behavior, &ShipParams, &mut Transform, &mut Acceleration),
+        Without<Rock>,
+    >,
+    rock_query: Query<(&Transform, &Collider), With<Rock>>,
+) {
+    let mut rng = rand::rng();
+    let delta = time.delta_secs();
+
+    for (behavior, ship, mut transform, mut acceleration) in ship_query.iter_mut() {
+        let position = transform.translation.truncate();
+        let forward = (transform.rotation * Vec3::Y).truncate();
+
+        let mut desired = match *behavior {
+            Behavior::Idle => Vec2::ZERO,
+            Behavior::Wander => wander(&mut rng, forward),
+            Behavior::Seek(target) => seek(position, target),
+            Behavior::Flee(threat) => flee(position, threat),
+            Behavior::Arrive(target) => arrive(position, target),
+        };
+        if desired != Vec2::ZERO {
+            let obstacles = rock_query
+                .iter_many(spatial_hash.nearby(position, AVOID_DISTANCE))
+                .map(|(rock_tf, rock_collider)| {
+                    (
+                        rock_tf.translation.truncate(),
+                        bounding_radius(rock_collider, rock_tf),
+                    )
+                });
+            desired = (desired + avoid(position, obstacles) * AVOID_WEIGHT).clamp_length_max(1.0);
+        }
+
+        acceleration.x = 0.0;
+        acceleration.y = 0.0;
+        if desired == Vec2::ZERO {
+            continue;
+        }
+
+        // the sprite faces up when not rotated
+        let (current, _, _) = transform.rotation.to_euler(EulerRot::ZYX);
+        let target = desired.to_angle() - FRAC_PI_2;
+        let diff = (target - current + PI).rem_euclid(TAU) - PI;
+        let max_turn = ship.turn_rate * delta;
+        transform.rotation = Quat::from_rotation_z(current + diff.clamp(-max_turn, max_turn));
+
+        // only thrust once roughly facing the right way
+        let forward = (transform.rotation * Vec3::Y).truncate();
+        let thrust = ship.thrust * desired.length() * forward.dot(desired.normalize()).max(0.0);
+        acceleration.x = forward.x * thrust;
+        acceleration.y = forward.y * thrust;
+    }
+}
+
+// ai ships bounce off rocks and get damaged like the player
+fn ship_collisions(
+    time: Res<Time>,
+    spatial_hash: Res<SpatialHash>,
+    mut ship_query: Query<
+        (&mut Transform, &Collider, &mut Velocity, &mut Hull),
+        (With<Behavior>, Without<Rock>),
+    >,
+    rock_query: Query<(&Transform, &Collider, &Velocity), (With<Rock>, Without<Behavior>)>,
+) {
+    for (mut ship_tf, ship_collider, mut ship_vel, mut hull) in ship_query.iter_mut() {
+        hull.cooldown.tick(time.delta());
+
+        let nearby = spatial_hash.nearby(
+            ship_tf.translation.truncate(),
+            bounding_radius(ship_collider, &ship_tf),
+        );
+        for (rock_tf, rock_collider, rock_vel) in rock_query.iter_many(nearby) {
+            let Some((normal, depth)) = collide(ship_collider, &ship_tf, rock_collider, rock_tf)
+            else {
+                continue;
+            };
+            ship_tf.translation -= (normal * depth).extend(0.0);
+
+            let closing =
+                (Vec2::new(ship_vel.x, ship_vel.y) - Vec2::new(rock_vel.x, rock_vel.y)).dot(normal);
+            if closing <= 0.0 {
+                continue;
+            }
+            if hull.cooldown.is_finished() {
+                hull.health -= closing * HULL_DAMAGE;
+                hull.cooldown.reset();
+            }
+            ship_vel.x -= (1.0 + SHIP_RESTITUTION) * closing * normal.x;
+            ship_vel.y -= (1.0 + SHIP_RESTITUTION) * closing * normal.y;
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use bevy::prelude::*;
+
+use crate::{
+    MAX_SHIP_STORAGE, ShipStorage, TRACTOR_PULL_SPEED, TRACTOR_RADIUS, TRACTOR_RADIUS_PER_LEVEL,
+    components::{Coal, Copper, Gold, Iron, Player, TractorBeam},
+};
+
+pub struct TractorPlugin;
+impl Plugin for TractorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedUpdate, tractor_pull);
+    }
+}
+
+impl TractorBeam {
+    pub fn radius(&self) -> f32 {
+        TRACTOR_RADIUS + self.level as f32 * TRACTOR_RADIUS_PER_LEVEL
+    }
+}
+
+// pull nearby ores towards the ship
+// no pull when the cargo hold is full
+fn tractor_pull(
+    time: Res<Time>,
+    ship_storage: Res<ShipStorage>,
+    mut gizmos: Gizmos,
+    player_query: Query<(&Transform, &TractorBeam), With<Player>>,
+    mut ore_query: Query<
+        &mut Transform,
+        (
+            Or<(With<Gold>, With<Iron>, With<Copper>, With<Coal>)>,
+            Without<Player>,
+        ),
+    >,
+) {
+    let Ok((player_tf, tractor)) = player_query.single() else {
+        return;
+    };
+
+    let mut ship_total = ship_storage.gold;
+    ship_total += ship_storage.iron;
+    ship_total += ship_storage.copper;
+    ship_total += ship_storage.coal;
+
+    if ship_total >= MAX_SHIP_STORAGE {
+        return;
+    }
+
+    let player_pos = player_tf.translation.truncate();
+    let radius = tractor.radius();
+    gizmos.circle_2d(player_pos, radius, Color::srgba(0.3, 0.8, 1.0, 0.15));
+
+    for mut ore_tf in ore_query.iter_mut() {
+        let ore_pos = ore_tf.translation.truncate();
+        let distance = ore_pos.distance(player_pos);
+        if distance > radius {
+            continue;
+        }
+
+        // pull harder the closer the ore gets
+        let pull = TRACTOR_PULL_SPEED * (1.0 - distance / radius * 0.5) * time.delta_secs();
+        let step = (player_pos - ore_pos).normalize_or_zero() * pull.min(distance);
+        ore_tf.translation += step.extend(0.0);
+
+        gizmos.line_2d(player_pos, ore_pos, Color::srgb(0.3, 0.8, 1.0));
+    }
+}
@@ -0,0 +1,205 @@
+use bevy::prelude::*;
+
+use crate::{
+    BASE_DOCK_COLLIDER, GameOver, GameTextures, MAX_TRADERS, SPRITE_SCALE, TRADER_CAPACITY,
+    TRADER_HULL, TRADER_SHIP, TRADER_SIGHT, TRADER_SPAWN_INTERVAL, TRADER_WANDER_TIME,
+    collision::{SpatialHash, bounding_radius, overlaps},
+    components::{Base, Coal, Collider, Copper, Gold, Hull, Iron, Movable, Trader},
+    mining::{ore_type, spill_ore},
+    player::player_movement,
+    steering::{Behavior, spawn_ai_ship, steer},
+    trades::ResourceList,
+};
+
+#[derive(Resource)]
+struct TraderSpawnTimer(Timer);
+
+pub struct TraderPlugin;
+impl Plugin for TraderPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(TraderSpawnTimer(Timer::from_seconds(
+            TRADER_SPAWN_INTERVAL,
+            TimerMode::Repeating,
+        )))
+        .add_systems(
+            FixedUpdate,
+            (
+                trader_spawn,
+                trader_behavior.before(steer),
+                trader_collect.after(player_movement),
+                trader_sell,
+                trader_destroyed,
+            ),
+        );
+    }
+}
+
+// traders launch from the base every now and then
+fn trader_spawn(
+    mut commands: Commands,
+    game_textures: Res<GameTextures>,
+    time: Res<Time>,
+    game_over: Res<GameOver>,
+    mut timer: ResMut<TraderSpawnTimer>,
+    base_query: Query<&Transform, With<Base>>,
+    trader_query: Query<(), With<Trader>>,
+) {
+    if game_over.0 || !timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
+    if trader_query.iter().len() >= MAX_TRADERS {
+        return;
+    }
+    let Ok(base_tf) = base_query.single() else {
+        return;
+    };
+
+    spawn_ai_ship(
+        &mut commands,
+        &game_textures,
+        TRADER_SHIP,
+        TRADER_HULL,
+        Color::srgb(0.5, 1.0, 0.6),
+        base_tf.translation.with_z(5.0),
+        SPRITE_SCALE,
+    )
+    .insert(Trader {
+        cargo: vec![],
+        wander: Timer::from_seconds(TRADER_WANDER_TIME, TimerMode::Once),
+    })
+    .insert(Behavior::Wander)
+    .insert(Movable {
+        auto_despawn: false,
+    });
+}
+
+// head for the nearest ore until the hold is full
+// then fly back to the base to sell, or go home after wandering too long
+fn trader_behavior(
+    time: Res<Time>,
+    base_query: Query<&Transform, With<Base>>,
+    ore_query: Query<&Transform, Or<(With<Gold>, With<Iron>, With<Copper>, With<Coal>)>>,
+    mut trader_query: Query<(&Transform, &mut Trader, &mut Behavior)>,
+) {
+    let Ok(base_tf) = base_query.single() else {
+        return;
+    };
+    let base_pos = base_tf.translation.truncate();
+
+    for (trader_tf, mut trader, mut behavior) in trader_query.iter_mut() {
+        let position = trader_tf.translation.truncate();
+
+        let mut nearest = TRADER_SIGHT;
+        let mut target = None;
+        for ore_tf in ore_query {
+            let distance = ore_tf.translation.truncate().distance(position);
+            if distance < nearest {
+                nearest = distance;
+                target = Some(ore_tf.translation.truncate());
+            }
+        }
+
+        let next = if trader.cargo.len() >= TRADER_CAPACITY {
+            Behavior::Arrive(base_pos)
+        } else if let Some(target) = target {
+            Behavior::Arrive(target)
+        } else if !trader.cargo.is_empty() {
+            Behavior::Arrive(base_pos)
+        } else if trader.wander.tick(time.delta()).is_finished() {
+            Behavior::Arrive(base_pos)
+        } else {
+            Behavior::Wander
+        };
+        behavior.set_if_neq(next);
+    }
+}
+
+// traders pick up ores from the same pool as the player
+// runs after the player's pickup so an ore is only ever collected once
+pub fn trader_collect(
+    mut commands: Commands,
+    spatial_hash: Res<SpatialHash>,
+    mut trader_query: Query<(&Transform, &Collider, &mut Trader)>,
+    ore_query: Query<
+        (
+            Entity,
+            &Transform,
+            &Collider,
+            Has<Gold>,
+            Has<Iron>,
+            Has<Copper>,
+        ),
+        Or<(With<Gold>, With<Iron>, With<Copper>, With<Coal>)>,
+    >,
+) {
+    let mut collected = vec![];
+
+    for (trader_tf, trader_collider, mut trader) in trader_query.iter_mut() {
+        if trader.cargo.len() >= TRADER_CAPACITY {
+            continue;
+        }
+        let nearby = spatial_hash.nearby(
+            trader_tf.translation.truncate(),
+            bounding_radius(trader_collider, trader_tf),
+        );
+        for (ore, ore_tf, ore_collider, is_gold, is_iron, is_copper) in ore_query.iter_many(&nearby)
+        {
+            if collected.contains(&ore)
+                || !overlaps(trader_collider, trader_tf, ore_collider, ore_tf)
+            {
+                continue;
+            }
+            commands.entity(ore).despawn();
+            collected.push(ore);
+            trader.cargo.push(ore_type(is_gold, is_iron, is_copper));
+            if trader.cargo.len() >= TRADER_CAPACITY {
+                break;
+            }
+        }
+    }
+}
+
+// selling at the base pushes market prices down
+// traders that gave up looking for ore dock for good
+fn trader_sell(
+    mut commands: Commands,
+    mut resource_list: ResMut<ResourceList>,
+    base_query: Query<&Transform, With<Base>>,
+    mut trader_query: Query<(Entity, &Transform, &Collider, &mut Trader)>,
+) {
+    let Ok(base_tf) = base_query.single() else {
+        return;
+    };
+
+    for (entity, trader_tf, trader_collider, mut trader) in trader_query.iter_mut() {
+        if !overlaps(trader_collider, trader_tf, &BASE_DOCK_COLLIDER, base_tf) {
+            continue;
+        }
+        for res_type in trader.cargo.drain(..) {
+            resource_list.flood(res_type);
+        }
+        if trader.wander.is_finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+// destroyed traders spill what they were carrying
+fn trader_destroyed(
+    mut commands: Commands,
+    game_textures: Res<GameTextures>,
+    trader_query: Query<(Entity, &Transform, &Trader, &Hull)>,
+) {
+    for (trader, trader_tf, cargo, hull) in trader_query {
+        if hull.health > 0.0 {
+            continue;
+        }
+        commands.entity(trader).despawn();
+        spill_ore(
+            &mut commands,
+            &game_textures,
+            cargo.cargo.iter().copied(),
+            trader_tf.translation,
+        );
+    }
+}
@@ -1,15 +1,34 @@
 use bevy::prelude::*;
 use bevy_egui::{EguiContexts, EguiPlugin, EguiPrimaryContextPass, egui};
 
-use crate::{BaseStorage, GameOver, PlayerCash, components::MarketTimer};
+use crate::{
+    BaseStorage, GameOver, PlayerCash, TRADER_PRICE_DROP, components::MarketTimer,
+    input::ActionState, keymap::Action,
+};
 
 use thousands::Separable;
 
-enum ResourceType {
+#[derive(Clone, Copy, PartialEq)]
+pub enum ResourceType {
     Gold,
     Iron,
     Copper,
     Coal,
+    Steel,
+    Wiring,
+}
+
+impl ResourceType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ResourceType::Gold => "Gold",
+            ResourceType::Iron => "Iron",
+            ResourceType::Copper => "Copper",
+            ResourceType::Coal => "Coal",
+            ResourceType::Steel => "Steel",
+            ResourceType::Wiring => "Wiring",
+        }
+    }
 }
 
 pub struct Resource {
@@ -21,6 +40,20 @@ pub struct Resource {
 #[derive(Resource)]
 pub struct ResourceList(Vec<Resource>);
 
+impl ResourceList {
+    // a rival sold one unit, the market is a little more saturated
+    pub fn flood(&mut self, res_type: ResourceType) {
+        for resource in &mut self.0 {
+            if resource.res_type == res_type {
+                resource.price -= resource.price / TRADER_PRICE_DROP;
+            }
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct ShowMarket(pub bool);
+
 pub struct TradesPlugin;
 impl Plugin for TradesPlugin {
     fn build(&self, app: &mut App) {
@@ -45,7 +78,18 @@ impl Plugin for TradesPlugin {
                 name: format!("Coal"),
                 price: 1200,
             },
+            Resource {
+                res_type: ResourceType::Steel,
+                name: format!("Steel"),
+                price: 2400,
+            },
+            Resource {
+                res_type: ResourceType::Wiring,
+                name: format!("Wiring"),
+                price: 4000,
+            },
         ]))
+        .insert_resource(ShowMarket(true))
         .add_plugins(EguiPlugin::default())
         .add_systems(EguiPrimaryContextPass, generate_market);
     }
@@ -57,6 +101,8 @@ fn generate_market(
     mut base: ResMut<BaseStorage>,
     mut cash: ResMut<PlayerCash>,
     mut resource_list: ResMut<ResourceList>,
+    mut show_market: ResMut<ShowMarket>,
+    actions: Res<ActionState>,
     time: Res<Time>,
     game_over: Res<GameOver>,
 ) -> Result {
@@ -67,36 +113,45 @@ fn generate_market(
         return Ok(());
     }
     market_timer.0.tick(time.delta());
-    egui::Window::new("Market").show(contexts.ctx_mut()?, |ui| {
+    if market_timer.0.is_finished() {
         for resource in &mut resource_list.0 {
-            if market_timer.0.is_finished() {
-                // update prices
-                // high chance of going up slow
-                // low chance of crash
-                use rand::Rng;
-                let mut rng = rand::rng();
+            // update prices
+            // high chance of going up slow
+            // low chance of crash
+            use rand::Rng;
+            let mut rng = rand::rng();
 
-                // chance to go up:
-                let positive = rng.random_bool(0.90);
+            // chance to go up:
+            let positive = rng.random_bool(0.90);
 
-                if positive {
-                    let price_factor = rng.random_range(30..100); // between 1% - 3%
-                    let change = resource.price / price_factor;
-                    resource.price += change;
-                } else {
-                    let change;
-                    match resource.res_type {
-                        ResourceType::Gold => {
-                            change = resource.price / 20; // -5%
-                        }
-                        _ => {
-                            change = resource.price / 10; // -10%
-                        }
+            if positive {
+                let price_factor = rng.random_range(30..100); // between 1% - 3%
+                let change = resource.price / price_factor;
+                resource.price += change;
+            } else {
+                let change;
+                match resource.res_type {
+                    ResourceType::Gold => {
+                        change = resource.price / 20; // -5%
+                    }
+                    _ => {
+                        change = resource.price / 10; // -10%
                     }
-                    resource.price -= change;
                 }
+                resource.price -= change;
             }
+        }
+    }
 
+    if actions.just_pressed(Action::ToggleMarket) {
+        show_market.0 = !show_market.0;
+    }
+    if !show_market.0 {
+        return Ok(());
+    }
+
+    egui::Window::new("Market").show(contexts.ctx_mut()?, |ui| {
+        for resource in &mut resource_list.0 {
             // generate ui
             ui.horizontal(|ui| {
                 if ui.button("Buy").clicked() {
@@ -115,6 +170,12 @@ fn generate_market(
                             ResourceType::Coal => {
                                 base.coal += 1;
                             }
+                            ResourceType::Steel => {
+                                base.steel += 1;
+                            }
+                            ResourceType::Wiring => {
+                                base.wiring += 1;
+                            }
                         }
                     }
                 }
@@ -144,6 +205,18 @@ fn generate_market(
                                 cash.0 += resource.price;
                             }
                         }
+                        ResourceType::Steel => {
+                            if base.steel >= 1 {
+                                base.steel -= 1;
+                                cash.0 += resource.price;
+                            }
+                        }
+                        ResourceType::Wiring => {
+                            if base.wiring >= 1 {
+                                base.wiring -= 1;
+                                cash.0 += resource.price;
+                            }
+                        }
                     }
                 }
                 ui.label(&resource.name);
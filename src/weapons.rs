@@ -0,0 +1,123 @@
+use bevy::prelude::*;
+
+use crate::{
+    GameOver, HARDPOINT_SPREAD, PLAYER_SIZE, PROJECTILE_COLLIDER, PROJECTILE_DAMAGE,
+    PROJECTILE_LIFETIME, PROJECTILE_SPEED, ROCK_HEAT_PER_DAMAGE, SPRITE_SCALE,
+    collision::{SpatialHash, bounding_radius, overlaps},
+    components::{
+        Collider, Heat, Hull, Movable, Pirate, Player, Projectile, Rock, Velocity, Weapon,
+    },
+    input::ShipInput,
+};
+
+pub struct WeaponPlugin;
+impl Plugin for WeaponPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (fire_weapons, projectile_lifetime, projectile_hits),
+        );
+    }
+}
+
+// every hardpoint fires one shot, fanned out around the nose
+fn fire_weapons(
+    mut commands: Commands,
+    time: Res<Time>,
+    ship_input: Res<ShipInput>,
+    game_over: Res<GameOver>,
+    mut player_query: Query<(&Transform, &Velocity, &mut Weapon), With<Player>>,
+) {
+    let Ok((player_tf, player_vel, mut weapon)) = player_query.single_mut() else {
+        return;
+    };
+    weapon.cooldown.tick(time.delta());
+    if game_over.0 || !ship_input.fire || !weapon.cooldown.is_finished() {
+        return;
+    }
+    weapon.cooldown.reset();
+
+    let forward = (player_tf.rotation * Vec3::Y).truncate();
+    let nose = player_tf.translation.truncate() + forward * PLAYER_SIZE.1 / 2.0 * SPRITE_SCALE;
+
+    for hardpoint in 0..weapon.level {
+        let angle = (hardpoint as f32 - (weapon.level - 1) as f32 / 2.0) * HARDPOINT_SPREAD;
+        let direction = Vec2::from_angle(angle).rotate(forward);
+
+        commands
+            .spawn((
+                Sprite::from_color(Color::srgb(1.0, 0.9, 0.4), Vec2::new(1.5, 4.0)),
+                Transform {
+                    translation: nose.extend(4.0),
+                    rotation: player_tf.rotation * Quat::from_rotation_z(angle),
+                    scale: Vec3::new(SPRITE_SCALE, SPRITE_SCALE, 1.),
+                },
+            ))
+            .insert(Projectile {
+                damage: PROJECTILE_DAMAGE,
+                lifetime: Timer::from_seconds(PROJECTILE_LIFETIME, TimerMode::Once),
+            })
+            .insert(Velocity {
+                x: player_vel.x + direction.x * PROJECTILE_SPEED,
+                y: player_vel.y + direction.y * PROJECTILE_SPEED,
+            })
+            .insert(Movable { auto_despawn: true })
+            .insert(PROJECTILE_COLLIDER);
+    }
+}
+
+fn projectile_lifetime(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut projectile_query: Query<(Entity, &mut Projectile)>,
+) {
+    for (projectile, mut shot) in projectile_query.iter_mut() {
+        if shot.lifetime.tick(time.delta()).just_finished() {
+            commands.entity(projectile).despawn();
+        }
+    }
+}
+
+// shots heat up rocks until they break and damage hostile ships
+fn projectile_hits(
+    mut commands: Commands,
+    spatial_hash: Res<SpatialHash>,
+    projectile_query: Query<(Entity, &Transform, &Collider, &Projectile)>,
+    mut rock_query: Query<(&Transform, &Collider, &mut Heat), (With<Rock>, Without<Projectile>)>,
+    mut pirate_query: Query<
+        (&Transform, &Collider, &mut Hull),
+        (With<Pirate>, Without<Projectile>),
+    >,
+) {
+    for (projectile, shot_tf, shot_collider, shot) in projectile_query {
+        let nearby = spatial_hash.nearby(
+            shot_tf.translation.truncate(),
+            bounding_radius(shot_collider, shot_tf),
+        );
+        let mut hit = false;
+
+        let mut rocks = rock_query.iter_many_mut(&nearby);
+        while let Some((rock_tf, rock_collider, mut heat)) = rocks.fetch_next() {
+            if overlaps(shot_collider, shot_tf, rock_collider, rock_tf) {
+                heat.0 += shot.damage * ROCK_HEAT_PER_DAMAGE;
+                hit = true;
+                break;
+            }
+        }
+
+        if !hit {
+            let mut pirates = pirate_query.iter_many_mut(&nearby);
+            while let Some((pirate_tf, pirate_collider, mut hull)) = pirates.fetch_next() {
+                if overlaps(shot_collider, shot_tf, pirate_collider, pirate_tf) {
+                    hull.health -= shot.damage;
+                    hit = true;
+                    break;
+                }
+            }
+        }
+
+        if hit {
+            commands.entity(projectile).despawn();
+        }
+    }
+}